@@ -0,0 +1,98 @@
+use clap::ValueEnum;
+
+// Charset is the character set used to render the text column
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Charset {
+    /// Printable ascii, 0x20 to 0x7e
+    Ascii,
+    /// EBCDIC, code page 037
+    Ebcdic,
+    /// UTF-8, multi byte chars are shown at their first byte
+    Utf8,
+}
+
+impl Charset {
+    // glyph returns the char of b in the charset if it is printable
+    pub fn glyph(self, b: u8) -> Option<char> {
+        match self {
+            Charset::Ascii | Charset::Utf8 => crate::is_printable(b).then_some(b as char),
+            Charset::Ebcdic => match EBCDIC_TABLE[b as usize] {
+                0 => None,
+                c => Some(char::from(c)),
+            },
+        }
+    }
+
+    // control_name returns the mnemonic of b if it is an ascii control char,
+    // there are none for EBCDIC.
+    pub fn control_name(self, b: u8) -> Option<&'static str> {
+        match (self, b) {
+            (Charset::Ebcdic, _) => None,
+            (_, 0x00..=0x1f) => Some(CONTROL_NAMES[b as usize]),
+            (_, 0x7f) => Some("DEL"),
+            _ => None,
+        }
+    }
+}
+
+// CONTROL_NAMES are the mnemonics of the ascii control chars 0x00 to 0x1f
+const CONTROL_NAMES: [&str; 32] = [
+    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "TAB", "LF", "VT", "FF", "CR",
+    "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
+    "FS", "GS", "RS", "US",
+];
+
+// utf8_glyphs returns the char to show for each byte of line, decoding it as
+// UTF-8. A printable multi byte char is shown at its first byte and None at
+// the rest, next holds the bytes following line for chars continuing past
+// it. Invalid sequences, and continuation bytes of a char started before
+// line, are None as well.
+pub fn utf8_glyphs(line: &[u8], next: &[u8]) -> Vec<Option<char>> {
+    let mut glyphs = Vec::with_capacity(line.len());
+    while glyphs.len() < line.len() {
+        let i = glyphs.len();
+        let len = match line[i] {
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            b => {
+                glyphs.push(Charset::Utf8.glyph(b));
+                continue;
+            }
+        };
+        let bytes: Vec<u8> = line[i..].iter().chain(next).take(len).copied().collect();
+        match std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|s| s.chars().next())
+        {
+            Some(c) if !c.is_control() && !c.is_whitespace() => {
+                glyphs.push(Some(c));
+                let rest = (len - 1).min(line.len() - glyphs.len());
+                glyphs.extend(std::iter::repeat_n(None, rest));
+            }
+            _ => glyphs.push(None),
+        }
+    }
+    glyphs
+}
+
+// EBCDIC_TABLE maps EBCDIC (code page 037) bytes to their latin-1 char, or
+// 0 for bytes without a printable char
+const EBCDIC_TABLE: [u8; 256] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x20, 0x00, 0xe2, 0xe4, 0xe0, 0xe1, 0xe3, 0xe5, 0xe7, 0xf1, 0xa2, 0x2e, 0x3c, 0x28, 0x2b, 0x7c,
+    0x26, 0xe9, 0xea, 0xeb, 0xe8, 0xed, 0xee, 0xef, 0xec, 0xdf, 0x21, 0x24, 0x2a, 0x29, 0x3b, 0xac,
+    0x2d, 0x2f, 0xc2, 0xc4, 0xc0, 0xc1, 0xc3, 0xc5, 0xc7, 0xd1, 0xa6, 0x2c, 0x25, 0x5f, 0x3e, 0x3f,
+    0xf8, 0xc9, 0xca, 0xcb, 0xc8, 0xcd, 0xce, 0xcf, 0xcc, 0x60, 0x3a, 0x23, 0x40, 0x27, 0x3d, 0x22,
+    0xd8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xab, 0xbb, 0xf0, 0xfd, 0xfe, 0xb1,
+    0xb0, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0xaa, 0xba, 0xe6, 0xb8, 0xc6, 0xa4,
+    0xb5, 0x7e, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0xa1, 0xbf, 0xd0, 0xdd, 0xde, 0xae,
+    0x5e, 0xa3, 0xa5, 0xb7, 0xa9, 0xa7, 0xb6, 0xbc, 0xbd, 0xbe, 0x5b, 0x5d, 0xaf, 0xa8, 0xb4, 0xd7,
+    0x7b, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x00, 0xf4, 0xf6, 0xf2, 0xf3, 0xf5,
+    0x7d, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51, 0x52, 0xb9, 0xfb, 0xfc, 0xf9, 0xfa, 0xff,
+    0x5c, 0xf7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0xb2, 0xd4, 0xd6, 0xd2, 0xd3, 0xd5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xb3, 0xdb, 0xdc, 0xd9, 0xda, 0x00,
+];
@@ -0,0 +1,81 @@
+use crate::{build_line, read_full, Dumper};
+use std::io::{Read, Write};
+
+impl Dumper {
+    // diff dumps the lines of a and b that differ, a's line prefixed by '-'
+    // and b's by '+', with the differing bytes highlighted, or put between
+    // brackets without color. Identical lines are prefixed by ' ', runs of
+    // them squeezed to '*' like all zero lines.
+    // Both readers are positioned past the skipped bytes and at the
+    // configured offset first.
+    pub fn diff<A: Read, B: Read, W: Write>(
+        &self,
+        mut a: A,
+        mut b: B,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let pos = self.start_pos()?;
+        let skipped_a = std::io::copy(&mut a.by_ref().take(pos), &mut std::io::sink())?;
+        let skipped_b = std::io::copy(&mut b.by_ref().take(pos), &mut std::io::sink())?;
+        let mut offset = skipped_a.min(skipped_b).saturating_sub(self.skip);
+        let fmt = self.line_format();
+        let mut buf_a = vec![0; self.cols];
+        let mut buf_b = vec![0; self.cols];
+        let mut last_was_same = false;
+        let mut skipped_lines = 0;
+
+        loop {
+            let mut n_a = read_full(&mut a, &mut buf_a)?;
+            let mut n_b = read_full(&mut b, &mut buf_b)?;
+            if self.limit != 0 {
+                let left = usize::try_from(self.limit.saturating_sub(offset)).unwrap_or(usize::MAX);
+                n_a = n_a.min(left);
+                n_b = n_b.min(left);
+            }
+            if n_a == 0 && n_b == 0 {
+                break;
+            }
+            let n = n_a.max(n_b);
+            offset += n as u64;
+
+            if n_a == n_b && buf_a[0..n_a] == buf_b[0..n_b] {
+                if self.squeeze && last_was_same && n == self.cols {
+                    skipped_lines += 1;
+                    continue;
+                }
+                if skipped_lines > 0 {
+                    self.print_skipped(out, skipped_lines, None)?;
+                    skipped_lines = 0;
+                }
+                write!(out, " ")?;
+                build_line(offset, &buf_a, n_a, &fmt, &[], &[]).print(out, &fmt)?;
+                last_was_same = true;
+                continue;
+            }
+
+            if skipped_lines > 0 {
+                self.print_skipped(out, skipped_lines, None)?;
+                skipped_lines = 0;
+            }
+            // a byte differs if the other file has a different byte, or none
+            let marks: Vec<bool> = (0..n)
+                .map(|i| i >= n_a || i >= n_b || buf_a[i] != buf_b[i])
+                .collect();
+            if n_a > 0 {
+                write!(out, "-")?;
+                build_line(offset - (n - n_a) as u64, &buf_a, n_a, &fmt, &marks, &[])
+                    .print(out, &fmt)?;
+            }
+            if n_b > 0 {
+                write!(out, "+")?;
+                build_line(offset - (n - n_b) as u64, &buf_b, n_b, &fmt, &marks, &[])
+                    .print(out, &fmt)?;
+            }
+            last_was_same = false;
+        }
+        if skipped_lines > 0 {
+            self.print_skipped(out, skipped_lines, None)?;
+        }
+        out.flush()
+    }
+}
@@ -0,0 +1,2770 @@
+use charset::{utf8_glyphs, Charset};
+use checksum::{crc16, crc8, Crc32, Sha256};
+use clap::ValueEnum;
+use flate2::read::GzDecoder;
+use scan::{shannon, Entropy, Histogram, Strings};
+use search::Matcher;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, SeekFrom, Stdin, Stdout};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub mod charset;
+pub mod checksum;
+mod diff;
+pub mod magic;
+pub mod scan;
+pub mod search;
+mod sparse;
+
+pub const LINE_BYTES: usize = 16;
+// number of lines read from the input at a time
+const BLOCK_LINES: usize = 4096;
+// time to wait before reading again at EOF when following the input
+const FOLLOW_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+// number of columns of a size rendered by human_size, like "1023.9 KiB"
+const HUMAN_WIDTH: usize = 10;
+// number of columns of a byte in the text column with ctrl_names
+const CTRL_CELL: usize = 3;
+// number of bytes dumped between updates of the progress indicator
+const PROGRESS_BYTES: u64 = 1 << 20;
+// number of lines printed before and after a matching line with search_only
+const SEARCH_CONTEXT: usize = 1;
+const PLAIN_LINE_BYTES: usize = 30;
+const C_LINE_BYTES: usize = 12;
+const BASE64_LINE_CHARS: usize = 76;
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    /// Offset, hex and ascii columns
+    Hexdump,
+    /// C array declaration, like 'xxd -i'
+    C,
+    /// Rust constant byte array declaration
+    Rust,
+    /// Python bytes literal, a line of the dump to each part of it
+    Python,
+    /// Go byte slice declaration
+    Go,
+    /// Standard base64, wrapped at 76 columns
+    Base64,
+    /// Like hexdump, with bytes as three digit octal values
+    Octal,
+    /// Like hexdump, with bytes as eight binary digits
+    Binary,
+    /// Like hexdump, with bytes as right justified decimal values, like 'od -t u1'
+    Decimal,
+    /// One JSON object per line with offset, hex and ascii fields
+    Json,
+    /// Comma separated offset, hex and ascii rows
+    Csv,
+    /// Like 'od -A x -t x1z', six digit offsets, ascii between '>' and '<'
+    /// and the end offset on a line of its own
+    Od,
+    /// Like 'xxd', offsets followed by ':', bytes in groups of two
+    Xxd,
+}
+
+impl Format {
+    // markers tells if the format shows seeking past the start or stopping
+    // before the end with the seek marker
+    fn markers(self) -> bool {
+        !matches!(self, Format::Od | Format::Xxd)
+    }
+}
+
+// Mode selects between dumping the bytes and scanning them for a summary
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mode {
+    Dump,
+    // Histogram prints the number of occurrences of each byte value
+    Histogram,
+    // Entropy prints the Shannon entropy of each block of the given size
+    Entropy(usize),
+    // Strings prints runs of printable ascii of at least the given length
+    Strings(usize),
+    // Count prints the number of occurrences of the given byte value
+    Count(u8),
+    // Find prints the offset of each occurrence of the search pattern
+    Find,
+    // Decode prints the bytes dumped decoded as a single value
+    Decode(Interpret),
+    // Carve writes the bytes as they are, to extract them into a file
+    Carve,
+}
+
+// Endian is the byte order of words, used by the hex column and by the
+// interpreted values. The ascii column is always in file order.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Endian {
+    /// Bytes in file order
+    Big,
+    /// Bytes of each word reversed
+    Little,
+}
+
+// LineCrc selects the checksum shown for the bytes of each line
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum LineCrc {
+    /// CRC-8, polynomial 0x07
+    Crc8,
+    /// CRC-16/CCITT-FALSE, polynomial 0x1021
+    Crc16,
+}
+
+impl LineCrc {
+    // checksum renders the checksum of bytes as hex digits
+    pub fn checksum(self, bytes: &[u8], upper: bool) -> String {
+        let s = match self {
+            LineCrc::Crc8 => format!("{:02x}", crc8(bytes)),
+            LineCrc::Crc16 => format!("{:04x}", crc16(bytes)),
+        };
+        if upper {
+            s.to_uppercase()
+        } else {
+            s
+        }
+    }
+}
+
+// Interpret selects how the words of a line are decoded into values
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Interpret {
+    /// Signed 8 bit integers
+    I8,
+    /// Signed 16 bit integers
+    I16,
+    /// Signed 32 bit integers
+    I32,
+    /// Signed 64 bit integers
+    I64,
+    /// IEEE-754 single precision floats
+    F32,
+    /// IEEE-754 double precision floats
+    F64,
+    /// The whole range as a little endian UTF-16 string
+    Utf16le,
+    /// The whole range as a big endian UTF-16 string
+    Utf16be,
+    /// The first word as a 32 bit Unix time, in UTC
+    Time32,
+    /// The first word as a 64 bit Unix time, in UTC
+    Time64,
+    /// The first word as an IPv4 address
+    Ipv4,
+    /// The first word as an IPv6 address, always in network byte order
+    Ipv6,
+    /// The first word as a UUID, in the byte order of RFC 4122
+    Uuid,
+    // Guid is a UUID with its first three fields little endian, as laid out
+    // by Microsoft GUIDs
+    #[value(skip)]
+    Guid,
+}
+
+impl Interpret {
+    // size is the number of bytes of a value
+    pub fn size(self) -> usize {
+        match self {
+            Interpret::I8 => 1,
+            Interpret::I16 | Interpret::Utf16le | Interpret::Utf16be => 2,
+            Interpret::I32 | Interpret::F32 | Interpret::Time32 => 4,
+            Interpret::I64 | Interpret::F64 | Interpret::Time64 => 8,
+            Interpret::Ipv4 => 4,
+            Interpret::Ipv6 | Interpret::Uuid | Interpret::Guid => 16,
+        }
+    }
+
+    // width is the number of columns needed to render any value
+    pub fn width(self) -> usize {
+        match self {
+            Interpret::I8 => 4,
+            Interpret::I16 => 6,
+            Interpret::I32 => 11,
+            Interpret::I64 => 20,
+            Interpret::F32 => 14,
+            Interpret::F64 => 24,
+            Interpret::Utf16le | Interpret::Utf16be => 1,
+            // a year of up to 11 digits, like "-2147483648-01-01 00:00:00 UTC"
+            Interpret::Time32 | Interpret::Time64 => 30,
+            Interpret::Ipv4 => 15,
+            Interpret::Ipv6 => 39,
+            Interpret::Uuid | Interpret::Guid => 36,
+        }
+    }
+
+    // region tells if the whole range is decoded as one value, instead of
+    // each word of a line
+    pub fn region(self) -> bool {
+        !matches!(
+            self,
+            Interpret::I8
+                | Interpret::I16
+                | Interpret::I32
+                | Interpret::I64
+                | Interpret::F32
+                | Interpret::F64
+        )
+    }
+
+    // decode renders word, of exactly size bytes, as a value read with the
+    // given byte order
+    pub fn decode(self, word: &[u8], endian: Endian) -> String {
+        let mut bytes = word.to_vec();
+        if endian == Endian::Little {
+            bytes.reverse();
+        }
+        match self {
+            Interpret::I8 => (bytes[0] as i8).to_string(),
+            Interpret::I16 => i16::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+            Interpret::I32 => i32::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+            Interpret::I64 => i64::from_be_bytes(bytes.try_into().unwrap()).to_string(),
+            Interpret::F32 => float(f32::from_be_bytes(bytes.try_into().unwrap())),
+            Interpret::F64 => float(f64::from_be_bytes(bytes.try_into().unwrap())),
+            Interpret::Time32 => utc(i32::from_be_bytes(bytes.try_into().unwrap()) as i64),
+            Interpret::Time64 => utc(i64::from_be_bytes(bytes.try_into().unwrap())),
+            Interpret::Ipv4 => Ipv4Addr::from(<[u8; 4]>::try_from(bytes).unwrap()).to_string(),
+            // ipv6 addresses have no other byte order than the network one
+            Interpret::Ipv6 => Ipv6Addr::from(<[u8; 16]>::try_from(word).unwrap()).to_string(),
+            Interpret::Uuid => uuid(word),
+            Interpret::Guid => {
+                let mut bytes = word.to_vec();
+                bytes[0..4].reverse();
+                bytes[4..6].reverse();
+                bytes[6..8].reverse();
+                uuid(&bytes)
+            }
+            Interpret::Utf16le | Interpret::Utf16be => String::from_utf16_lossy(&[match self {
+                Interpret::Utf16le => u16::from_le_bytes([word[0], word[1]]),
+                _ => u16::from_be_bytes([word[0], word[1]]),
+            }]),
+        }
+    }
+
+    // decode_region renders bytes, the whole range dumped, as a single
+    // value. Invalid UTF-16, and an odd byte at the end, are decoded as
+    // replacement chars, the other types decode the first word and fail if
+    // the range is shorter than that.
+    pub fn decode_region(self, bytes: &[u8], endian: Endian) -> std::io::Result<String> {
+        let unit = |pair: &[u8]| match self {
+            Interpret::Utf16le => u16::from_le_bytes([pair[0], pair[1]]),
+            _ => u16::from_be_bytes([pair[0], pair[1]]),
+        };
+        match self {
+            Interpret::Utf16le | Interpret::Utf16be => {
+                let units: Vec<u16> = bytes.chunks_exact(2).map(unit).collect();
+                let mut s = String::from_utf16_lossy(&units);
+                if bytes.len() % 2 == 1 {
+                    s.push(char::REPLACEMENT_CHARACTER);
+                }
+                Ok(s)
+            }
+            _ if bytes.len() < self.size() => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{} bytes left to decode, a value needs {}",
+                    bytes.len(),
+                    self.size()
+                ),
+            )),
+            _ => Ok(self.decode(&bytes[0..self.size()], endian)),
+        }
+    }
+}
+
+// float renders v in the shortest form that reads back as v, switching to
+// exponent notation for very small and very large magnitudes
+fn float<F: Into<f64> + Copy + std::fmt::Display + std::fmt::LowerExp>(v: F) -> String {
+    let m = v.into().abs();
+    if m == 0.0 || !m.is_finite() || (1e-4..1e7).contains(&m) {
+        v.to_string()
+    } else {
+        format!("{:e}", v)
+    }
+}
+
+// uuid renders the 16 bytes as a canonical UUID, like
+// 123e4567-e89b-12d3-a456-426614174000
+fn uuid(bytes: &[u8]) -> String {
+    let hex = |r: std::ops::Range<usize>| -> String {
+        bytes[r].iter().map(|b| format!("{:02x}", b)).collect()
+    };
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex(0..4),
+        hex(4..6),
+        hex(6..8),
+        hex(8..10),
+        hex(10..16)
+    )
+}
+
+// INTERRUPTED is set on SIGINT while following the input, ending the dump
+// with the bytes of the last, partial, line.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// catch_interrupt sets INTERRUPTED on SIGINT instead of exiting
+#[cfg(unix)]
+fn catch_interrupt() {
+    extern "C" fn interrupt(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+    }
+    // SAFETY: the handler only stores to an atomic, which is signal safe
+    unsafe {
+        libc::signal(libc::SIGINT, interrupt as *const () as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+fn catch_interrupt() {}
+
+// read_full reads into buf until it is full or the reader is at EOF,
+// returning the number of bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..])? {
+            0 => break,
+            read => n += read,
+        }
+    }
+    Ok(n)
+}
+
+// utc renders secs, seconds since the Unix epoch, as a UTC date and time
+fn utc(secs: i64) -> String {
+    let days = secs.div_euclid(86400);
+    let time = secs.rem_euclid(86400);
+    // the civil date of days, counting years from March so leap days come last
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+// Radix is the base used when rendering bytes and offsets in a line
+#[derive(Clone, Copy, PartialEq)]
+pub enum Radix {
+    Hex,
+    Octal,
+    Binary,
+    Decimal,
+}
+
+impl Radix {
+    // width is the number of digits used to render a single byte
+    pub fn width(self) -> usize {
+        match self {
+            Radix::Hex => 2,
+            Radix::Octal => 3,
+            Radix::Binary => 8,
+            Radix::Decimal => 3,
+        }
+    }
+
+    // prefix is put before an offset to mark its radix, decimal has none
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Radix::Hex => "0x",
+            Radix::Octal => "0o",
+            Radix::Binary => "0b",
+            Radix::Decimal => "",
+        }
+    }
+
+    // offset_width is the default number of digits of a rendered offset
+    pub fn offset_width(self) -> usize {
+        match self {
+            Radix::Binary => 32,
+            _ => 8,
+        }
+    }
+
+    // digits is the number of digits needed to render n
+    pub fn digits(self, n: u64) -> usize {
+        let base = match self {
+            Radix::Hex => 16,
+            Radix::Octal => 8,
+            Radix::Binary => 2,
+            Radix::Decimal => 10,
+        };
+        let mut digits = 1;
+        let mut n = n / base;
+        while n > 0 {
+            digits += 1;
+            n /= base;
+        }
+        digits
+    }
+}
+
+// LineFormat holds the settings of dumper controlling how a line is
+// rendered, along with the widths derived from them
+pub struct LineFormat<'a> {
+    pub dumper: &'a Dumper,
+    pub hex_length: usize,
+    pub radix: Radix,
+    // offset_width is the width of the offsets, resolved from the default
+    pub offset_width: usize,
+    // group is the number of bytes between separators, a multiple of
+    // word_size
+    pub group: usize,
+    pub values_length: usize,
+    // origin is subtracted from the offsets printed
+    pub origin: u64,
+    // line_start is the offset of the line numbered one with line_numbers
+    pub line_start: u64,
+}
+
+pub struct Line {
+    pub ascii: Option<String>,
+    pub values: Option<String>,
+    pub crc: Option<String>,
+    // strings notes the printable strings found in the line
+    pub strings: Option<String>,
+    pub hex: String,
+    pub hex_width: usize,
+    pub start_offset: u64,
+}
+
+impl LineFormat<'_> {
+    // escapes tells if ANSI escapes may be used to color bytes
+    fn escapes(&self) -> bool {
+        self.dumper.color || self.dumper.dim_zeros || self.dumper.entropy_color
+    }
+
+    // group_width is the number of columns of a group of bytes in the hex
+    // column, with nibbles the halves of a byte are separated by a space and
+    // the bytes of a group by two
+    fn group_width(&self, bytes: usize) -> usize {
+        match self.dumper.nibbles {
+            true => bytes * (self.radix.width() + 3) - 2,
+            false => bytes * self.radix.width(),
+        }
+    }
+
+    // split_before tells if the columns are split before the byte at pos
+    fn split_before(&self, pos: usize) -> bool {
+        self.dumper.split_at > 0 && pos > 0 && pos.is_multiple_of(self.dumper.split_at)
+    }
+
+    // shown is the offset printed for offset
+    fn shown(&self, offset: u64) -> u64 {
+        self.dumper.base_offset + offset - self.origin
+    }
+
+    // offset_prefix is what precedes the offset of a line
+    fn offset_prefix(&self) -> &'static str {
+        match self.dumper.offset_prefix {
+            true => self.dumper.offset_radix.prefix(),
+            false => "",
+        }
+    }
+
+    // offset_separator is what follows the offset of a line
+    fn offset_separator(&self) -> &'static str {
+        match self.dumper.format {
+            Format::Od => " ",
+            Format::Xxd => ": ",
+            _ => "  ",
+        }
+    }
+
+    // line_width is the number of columns of a full line, not counting the
+    // brackets around highlighted bytes
+    pub fn line_width(&self) -> usize {
+        let mut width = 0;
+        if self.dumper.line_numbers {
+            width += self.offset_width + self.offset_separator().len();
+        } else if self.dumper.offsets {
+            width += self.offset_prefix().len() + self.offset_width + self.offset_separator().len();
+            if self.dumper.dual_offset > 0 {
+                width += self.dumper.dual_offset + 3;
+            }
+            if self.dumper.human_offset {
+                width += HUMAN_WIDTH + 3;
+            }
+        }
+        if self.dumper.hex {
+            width += self.hex_length;
+            if self.dumper.interpret.is_some() {
+                width += 2 + self.values_length;
+            }
+            width += 2;
+        }
+        if self.dumper.ascii {
+            let delimiters = match self.dumper.format {
+                Format::Xxd => 0,
+                _ => 2,
+            };
+            width += delimiters + self.dumper.cols;
+            if self.dumper.ctrl_names {
+                width += self.dumper.cols * CTRL_CELL - 1;
+            }
+            width += (self.dumper.cols - 1)
+                .checked_div(self.dumper.split_at)
+                .unwrap_or(0);
+        }
+        match self.dumper.line_crc {
+            Some(LineCrc::Crc8) => width + 3,
+            Some(LineCrc::Crc16) => width + 5,
+            None => width,
+        }
+    }
+
+    // print_ruler prints a header numbering the bytes of a line of cols
+    // bytes, aligned with the columns of the lines below it. Each group is
+    // labeled with the position of its first byte, the ascii column with the
+    // last digit of each position.
+    pub fn print_ruler<W: Write>(&self, out: &mut W, cols: usize) -> std::io::Result<()> {
+        let positions: Vec<usize> = (0..cols).collect();
+        let mut hex = String::new();
+        for (i, word) in positions.chunks(self.group).enumerate() {
+            if i > 0 && self.dumper.nibbles {
+                hex.push(' ');
+            }
+            if i > 0 {
+                hex += &self.dumper.group_sep;
+            }
+            if self.split_before(word[0]) {
+                hex += "|";
+                hex += &self.dumper.group_sep;
+            }
+            let label = if self.dumper.upper {
+                format!("{:02X}", word[0])
+            } else {
+                format!("{:02x}", word[0])
+            };
+            let w = self.group_width(word.len());
+            hex += &format!("{:<w$}", label);
+        }
+        let digit = |p: &usize| {
+            let d = char::from_digit((p % 16) as u32, 16).unwrap();
+            if self.dumper.upper {
+                d.to_ascii_uppercase()
+            } else {
+                d
+            }
+        };
+        let mut ascii = String::new();
+        for p in &positions {
+            if self.dumper.ctrl_names && *p > 0 {
+                ascii.push(' ');
+            }
+            if self.split_before(*p) {
+                ascii.push('|');
+            }
+            match self.dumper.ctrl_names {
+                true => ascii += &format!("{:>w$}", digit(p), w = CTRL_CELL),
+                false => ascii.push(digit(p)),
+            }
+        }
+        let ruler = Line {
+            ascii: self.dumper.ascii.then_some(ascii),
+            values: self.dumper.interpret.map(|_| String::new()),
+            crc: None,
+            strings: None,
+            hex_width: hex.chars().count(),
+            hex,
+            start_offset: 0,
+        };
+        if self.dumper.offsets {
+            let mut w =
+                self.offset_prefix().len() + self.offset_width + self.offset_separator().len();
+            if self.dumper.dual_offset > 0 {
+                // the decimal offset between parentheses
+                w += self.dumper.dual_offset + 3;
+            }
+            if self.dumper.human_offset {
+                w += HUMAN_WIDTH + 3;
+            }
+            write!(out, "{: <w$}", "")?;
+        }
+        ruler.print_columns(out, self)
+    }
+}
+
+impl Line {
+    // print prints the line, a line without any bytes, as used to show the
+    // end offset after squeezed lines, is printed as just the offset.
+    pub fn print<W: Write>(&self, out: &mut W, fmt: &LineFormat) -> std::io::Result<()> {
+        if fmt.dumper.line_numbers {
+            // there is no line to number at the end offset
+            if self.hex.is_empty() {
+                return Ok(());
+            }
+            let line = (self.start_offset - fmt.line_start) / fmt.dumper.cols as u64 + 1;
+            write!(
+                out,
+                "{:>w$}{}",
+                line,
+                fmt.offset_separator(),
+                w = fmt.offset_width
+            )?;
+        } else if fmt.dumper.offsets {
+            let (offset, w) = (fmt.shown(self.start_offset), fmt.offset_width);
+            write!(out, "{}", fmt.offset_prefix())?;
+            match fmt.dumper.offset_radix {
+                // xxd keeps offsets in lowercase
+                Radix::Hex if fmt.dumper.upper && fmt.dumper.format != Format::Xxd => {
+                    write!(out, "{:0w$X}", offset)?
+                }
+                Radix::Hex => write!(out, "{:0w$x}", offset)?,
+                Radix::Octal => write!(out, "{:0w$o}", offset)?,
+                Radix::Binary => write!(out, "{:0w$b}", offset)?,
+                Radix::Decimal => write!(out, "{:0w$}", offset)?,
+            }
+            if fmt.dumper.dual_offset > 0 {
+                write!(out, " ({:>w$})", offset, w = fmt.dumper.dual_offset)?;
+            }
+            if fmt.dumper.human_offset {
+                write!(out, " ({:>w$})", human_size(offset), w = HUMAN_WIDTH)?;
+            }
+            if self.hex.is_empty() {
+                return writeln!(out);
+            }
+            write!(out, "{}", fmt.offset_separator())?;
+        } else if self.hex.is_empty() {
+            return Ok(());
+        }
+        self.print_columns(out, fmt)
+    }
+
+    // print_columns prints the hex, values and ascii columns of the line
+    fn print_columns<W: Write>(&self, out: &mut W, fmt: &LineFormat) -> std::io::Result<()> {
+        let (open, close) = match fmt.dumper.format {
+            Format::Od => (">", "<"),
+            Format::Xxd => ("", ""),
+            _ => ("|", "|"),
+        };
+        if !fmt.dumper.hex {
+            if let Some(ascii) = &self.ascii {
+                write!(out, "{}{}{}", open, ascii, close)?;
+            }
+            return self.print_end(out, " ");
+        }
+        write!(out, "{}", self.hex)?;
+        // pad on visible width, as hex may contain color escapes
+        let mut pad = fmt.hex_length.saturating_sub(self.hex_width);
+        if let Some(values) = &self.values {
+            write!(out, "{: <pad$}  {}", "", values)?;
+            pad = fmt.values_length.saturating_sub(values.len());
+        }
+        match &self.ascii {
+            Some(ascii) => {
+                write!(out, "{: <pad$}  {}{}{}", "", open, ascii, close)?;
+                self.print_end(out, " ")
+            }
+            None => self.print_end(out, &format!("{: <pad$}  ", "")),
+        }
+    }
+
+    // print_end ends the line with the line checksum, if any, after sep,
+    // followed by the strings found in the line
+    fn print_end<W: Write>(&self, out: &mut W, sep: &str) -> std::io::Result<()> {
+        if let Some(crc) = &self.crc {
+            write!(out, "{}{}", sep, crc)?;
+        }
+        match &self.strings {
+            Some(strings) => writeln!(out, "  ; {}", strings),
+            None => writeln!(out),
+        }
+    }
+}
+
+// Input is where bytes are read from, either a regular file or stdin, or
+// either of them decompressed with gzip
+pub enum Input {
+    File(File),
+    Stdin(Stdin),
+    Gzip(GzDecoder<Box<dyn Read>>),
+}
+
+impl Input {
+    pub fn open(filename: &str) -> std::io::Result<Input> {
+        if filename == "-" {
+            Ok(Input::Stdin(std::io::stdin()))
+        } else {
+            File::open(filename).map(Input::File)
+        }
+    }
+
+    // gunzip wraps the input in a gzip decoder, the result is not seekable
+    pub fn gunzip(self) -> Input {
+        match self {
+            Input::File(f) => Input::Gzip(GzDecoder::new(Box::new(f))),
+            Input::Stdin(s) => Input::Gzip(GzDecoder::new(Box::new(s))),
+            Input::Gzip(_) => self,
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Input::File(f) => f.read(buf),
+            Input::Stdin(s) => s.read(buf),
+            Input::Gzip(g) => g.read(buf),
+        }
+    }
+}
+
+// Output is where bytes are written to, either a regular file or stdout
+pub enum Output {
+    File(File),
+    Stdout(Stdout),
+}
+
+impl Output {
+    pub fn create(filename: Option<&str>) -> std::io::Result<Output> {
+        match filename {
+            Some(name) => File::create(name).map(Output::File),
+            None => Ok(Output::Stdout(std::io::stdout())),
+        }
+    }
+
+    // skip_to will move the write position from cur to pos, seeking if the
+    // output supports it, otherwise writing zeros to fill the gap.
+    pub fn skip_to(&mut self, cur: u64, pos: u64) -> std::io::Result<()> {
+        match self {
+            Output::File(f) => {
+                // extend the file when moving past its end
+                if pos > f.metadata()?.len() {
+                    f.set_len(pos)?;
+                }
+                f.seek(SeekFrom::Start(pos)).map(|_| ())
+            }
+            Output::Stdout(s) => {
+                if pos < cur {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("cannot move back to offset {:08x} on stdout", pos),
+                    ));
+                }
+                std::io::copy(&mut std::io::repeat(0).take(pos - cur), s).map(|_| ())
+            }
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Output::File(f) => f.write(buf),
+            Output::Stdout(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::File(f) => f.flush(),
+            Output::Stdout(s) => s.flush(),
+        }
+    }
+}
+
+// Dumper holds the configuration of a dump, the dump itself is done by
+// dump for seekable readers, or dump_unseekable for streams like stdin.
+pub struct Dumper {
+    pub word_size: usize,
+    pub cols: usize,
+    // skip is the number of bytes at the start of the input ignored
+    // altogether, offsets and the limit count from the byte following them
+    pub skip: u64,
+    pub offset: Option<u64>,
+    // limit is the offset at which to stop reading, 0 means no limit
+    pub limit: u64,
+    // squeeze replaces runs of identical lines by a single squeeze_marker
+    pub squeeze: bool,
+    pub squeeze_marker: String,
+    // squeeze_count adds the number of bytes skipped to the squeeze_marker
+    pub squeeze_count: bool,
+    // rle adds the byte and the length of the run it fills to the
+    // squeeze_marker of lines repeating a single byte
+    pub rle: bool,
+    // seek_marker is printed when starting after, or stopping before, the
+    // end of the input
+    pub seek_marker: String,
+    pub format: Format,
+    pub plain: bool,
+    pub offset_radix: Radix,
+    // offset_width is the number of digits of offsets, 0 for the default
+    pub offset_width: usize,
+    // offsets enables the offset column of the hexdump formats
+    pub offsets: bool,
+    // offset_prefix puts the radix prefix, like 0x, before offsets
+    pub offset_prefix: bool,
+    // line_numbers prints the number of each line, from 1, in place of its
+    // offset
+    pub line_numbers: bool,
+    // dual_offset is the number of digits of the decimal offset following
+    // each offset, 0 for none
+    pub dual_offset: usize,
+    // human_offset follows each offset by its size in KiB, MiB or GiB
+    pub human_offset: bool,
+    // base_offset is added to the offsets printed, as when continuing the
+    // offsets of a previous file
+    pub base_offset: u64,
+    // relative prints offsets from the start of the dump instead of the
+    // start of the input
+    pub relative: bool,
+    pub upper: bool,
+    // nibbles splits each byte of the hex column in two halves, of a hex
+    // digit or four binary digits each
+    pub nibbles: bool,
+    // hex shows the hex column, without it only the ascii column is shown
+    pub hex: bool,
+    pub ascii: bool,
+    // charset is used to render the bytes of the text column
+    pub charset: Charset,
+    // ctrl_names shows control chars by their mnemonic in the text column,
+    // widening every byte to a cell of CTRL_CELL columns
+    pub ctrl_names: bool,
+    // unprintable is shown in the text column for bytes without a char
+    pub unprintable: char,
+    // group_sep separates the groups of the hex column
+    pub group_sep: String,
+    // group is the number of bytes shown together between separators, a
+    // multiple of word_size, 0 to group each word on its own
+    pub group: usize,
+    // split_at inserts a bar into the hex and ascii columns every split_at
+    // bytes, a multiple of the group size, 0 for none
+    pub split_at: usize,
+    // interpret adds a column with the words decoded as values
+    pub interpret: Option<Interpret>,
+    // highlight marks every occurrence of the byte, in inverse video with
+    // color or between brackets without
+    pub highlight: Option<u8>,
+    pub color: bool,
+    // dim_zeros shows null bytes dimmed, without coloring any other bytes
+    pub dim_zeros: bool,
+    // entropy_color tints the hex column of each line by the entropy of its
+    // bytes, from blue to red, without coloring the bytes by class
+    pub entropy_color: bool,
+    pub endian: Endian,
+    // line_crc appends a checksum of the bytes of each line
+    pub line_crc: Option<LineCrc>,
+    // inline_strings appends the printable strings of at least this many
+    // bytes found in each line, with their offsets, 0 for none
+    pub inline_strings: usize,
+    // escape_all escapes every byte of the python format, not just the
+    // unprintable ones
+    pub escape_all: bool,
+    // name is the variable name used by the c, rust, python and go formats
+    pub name: String,
+    // header enables the header row of the csv format
+    pub header: bool,
+    // crc32 prints a CRC32 of the dumped bytes after the last line
+    pub crc32: bool,
+    // sha256 prints a SHA-256 digest of the dumped bytes after the last line
+    pub sha256: bool,
+    // expect_crc32 and expect_sha256 check the CRC32 and SHA-256 digest of
+    // the dumped bytes, failing the dump if either does not match
+    pub expect_crc32: Option<u32>,
+    pub expect_sha256: Option<[u8; 32]>,
+    pub mode: Mode,
+    // width is the number of columns available for bar charts
+    pub width: usize,
+    // search is a byte pattern to highlight in the dump, empty for none
+    pub search: Vec<u8>,
+    // search_only prints only the lines matching search, with context
+    pub search_only: bool,
+    // start_at is a byte pattern the dump starts at, the bytes before it are
+    // skipped, empty for none
+    pub start_at: Vec<u8>,
+    // stop_at is a byte pattern ending the dump with the line it ends on,
+    // empty for none
+    pub stop_at: Vec<u8>,
+    // ruler prints a header numbering the bytes of a line
+    pub ruler: bool,
+    // identify prints the file type guessed from the first bytes dumped
+    pub identify: bool,
+    // summary prints the range of bytes dumped to stderr at the end
+    pub summary: bool,
+    // annotations are the names of offsets, sorted by offset, printed as
+    // comments before the lines holding them
+    pub annotations: Vec<(u64, String)>,
+    // progress shows the progress of the dump on stderr, given the number of
+    // bytes to dump, 0 if unknown
+    pub progress: Option<u64>,
+    // follow keeps reading at EOF, dumping bytes appended to the input
+    pub follow: bool,
+}
+
+impl Default for Dumper {
+    fn default() -> Self {
+        Dumper {
+            word_size: 1,
+            cols: LINE_BYTES,
+            skip: 0,
+            offset: None,
+            limit: 0,
+            squeeze: true,
+            squeeze_marker: "*".to_string(),
+            squeeze_count: false,
+            rle: false,
+            seek_marker: "**".to_string(),
+            format: Format::Hexdump,
+            plain: false,
+            offset_radix: Radix::Hex,
+            offset_width: 0,
+            offsets: true,
+            offset_prefix: false,
+            line_numbers: false,
+            dual_offset: 0,
+            human_offset: false,
+            base_offset: 0,
+            relative: false,
+            upper: false,
+            nibbles: false,
+            hex: true,
+            ascii: true,
+            charset: Charset::Ascii,
+            ctrl_names: false,
+            unprintable: '.',
+            group_sep: " ".to_string(),
+            group: 0,
+            split_at: 0,
+            interpret: None,
+            highlight: None,
+            color: false,
+            dim_zeros: false,
+            entropy_color: false,
+            endian: Endian::Big,
+            line_crc: None,
+            inline_strings: 0,
+            name: "data".to_string(),
+            header: true,
+            crc32: false,
+            sha256: false,
+            expect_crc32: None,
+            expect_sha256: None,
+            mode: Mode::Dump,
+            width: 80,
+            search: Vec::new(),
+            start_at: Vec::new(),
+            stop_at: Vec::new(),
+            escape_all: false,
+            search_only: false,
+            ruler: false,
+            identify: false,
+            summary: false,
+            annotations: Vec::new(),
+            progress: None,
+            follow: false,
+        }
+    }
+}
+
+impl Dumper {
+    // line_format derives the rendering settings of a line from the dumper
+    pub fn line_format(&self) -> LineFormat<'_> {
+        let radix = match self.format {
+            Format::Octal => Radix::Octal,
+            Format::Binary => Radix::Binary,
+            Format::Decimal => Radix::Decimal,
+            _ => Radix::Hex,
+        };
+        // the last word of a line is short if word_size does not divide cols
+        let line_words = self.cols.div_ceil(self.word_size);
+        let group = match self.group {
+            0 => self.word_size,
+            g => g,
+        };
+        let groups = self.cols.div_ceil(group);
+        let mut hex_length =
+            self.cols * radix.width() + self.group_sep.chars().count() * groups.saturating_sub(1);
+        if self.nibbles {
+            // the space within each byte, the two between the bytes of a
+            // group and the one widening each group separator
+            hex_length += 3 * self.cols - groups - 1;
+        }
+        if let Some(splits) = (self.cols - 1).checked_div(self.split_at) {
+            // a bar and another separator at each split
+            hex_length += splits * (1 + self.group_sep.chars().count());
+        }
+        LineFormat {
+            dumper: self,
+            hex_length,
+            radix,
+            offset_width: match self.offset_width {
+                0 => self.offset_radix.offset_width(),
+                w => w,
+            },
+            group,
+            values_length: self
+                .interpret
+                .map_or(0, |i| (i.width() + 1) * line_words - 1),
+            origin: 0,
+            line_start: 0,
+        }
+    }
+
+    // print_identity prints the file type guessed from head, the first bytes
+    // of the dump
+    fn print_identity<W: Write>(&self, out: &mut W, head: &[u8]) -> std::io::Result<()> {
+        writeln!(out, "type: {}", magic::identify(head).unwrap_or("unknown"))
+    }
+
+    // report_start reports where the start_at pattern was found, if it was,
+    // passing on found
+    fn report_start(&self, found: Option<u64>) -> Option<u64> {
+        match found {
+            Some(start) => eprintln!("found start pattern at offset 0x{:x}", self.skip + start),
+            None => eprintln!("start pattern not found"),
+        }
+        found
+    }
+
+    // print_skipped prints the squeeze marker standing in for a run of lines,
+    // fill is the byte filling the lines if they repeat a single one
+    fn print_skipped<W: Write>(
+        &self,
+        out: &mut W,
+        lines: usize,
+        fill: Option<u8>,
+    ) -> std::io::Result<()> {
+        let bytes = lines as u64 * self.cols as u64;
+        match fill {
+            _ if !self.squeeze_count => writeln!(out, "{}", self.squeeze_marker),
+            Some(b) => writeln!(
+                out,
+                "{} (skipped 0x{:x} bytes of {:02x})",
+                self.squeeze_marker, bytes, b
+            ),
+            None => writeln!(out, "{} (skipped 0x{:x} bytes)", self.squeeze_marker, bytes),
+        }
+    }
+
+    // start_pos returns the position in the input of the configured offset,
+    // past the skipped bytes
+    pub fn start_pos(&self) -> std::io::Result<u64> {
+        let offset = self.offset.unwrap_or(0);
+        self.skip.checked_add(offset).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "can not seek to offset 0x{:x} past 0x{:x} skipped bytes",
+                    offset, self.skip
+                ),
+            )
+        })
+    }
+
+    // dump seeks reader to the configured offset and dumps it to out
+    pub fn dump<R: Read + Seek, W: Write>(
+        &self,
+        mut reader: R,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let mut start: u64 = 0;
+        if self.offset.is_some() || self.skip > 0 {
+            let pos = self.start_pos()?;
+            start = reader.seek(SeekFrom::Start(pos))?.saturating_sub(self.skip);
+        }
+        self.dump_from(reader, out, start)
+    }
+
+    // dump_unseekable is dump for readers not able to seek, it will read
+    // and discard bytes up to the configured offset instead.
+    pub fn dump_unseekable<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let pos = self.start_pos()?;
+        let discarded = std::io::copy(&mut reader.by_ref().take(pos), &mut std::io::sink())?;
+        self.dump_from(reader, out, discarded.saturating_sub(self.skip))
+    }
+
+    // dump_slice dumps bytes already in memory, like a memory mapped file,
+    // without copying them through a read buffer.
+    pub fn dump_slice<W: Write>(&self, data: &[u8], out: &mut W) -> std::io::Result<()> {
+        let data = &data[usize::try_from(self.skip).map_or(data.len(), |s| s.min(data.len()))..];
+        let mut end = data.len();
+        if self.limit != 0 {
+            end = end.min(usize::try_from(self.limit).unwrap_or(usize::MAX));
+        }
+        let mut start = match self.offset {
+            Some(pos) => usize::try_from(pos).unwrap_or(usize::MAX).min(end),
+            None => 0,
+        };
+        if !self.start_at.is_empty() {
+            let found = data[start..end]
+                .windows(self.start_at.len())
+                .position(|w| w == self.start_at);
+            match self.report_start(found.map(|p| (start + p) as u64)) {
+                Some(found) => start = found as usize,
+                None => return Ok(()),
+            }
+        }
+        if self.identify {
+            self.print_identity(out, &data[start..])?;
+        }
+        let mut dump = Dump::new(self, start as u64);
+        dump.start(out)?;
+        for line in data[start..end].chunks(self.cols) {
+            if dump.line(out, line, line.len())? {
+                return dump.finish(out);
+            }
+        }
+        if dump.skipped_lines > 0 {
+            dump.line(out, &[], 0)?;
+        }
+        dump.finish(out)
+    }
+
+    // dump_from runs the read loop on a reader positioned at start. The
+    // reader is buffered in blocks of whole lines, so the lines handed to the
+    // loop stay aligned when refilling the buffer.
+    fn dump_from<R: Read, W: Write>(
+        &self,
+        reader: R,
+        out: &mut W,
+        mut start: u64,
+    ) -> std::io::Result<()> {
+        // never read past the limit, stopping exactly at it, the start may
+        // already be past it
+        let left = match self.limit {
+            0 => u64::MAX,
+            limit => limit.saturating_sub(start),
+        };
+        let mut reader = BufReader::with_capacity(self.cols * BLOCK_LINES, reader.take(left));
+        // head holds bytes already consumed from the reader, that are to be
+        // dumped before reading any more
+        let mut head: &[u8] = &[];
+        if !self.start_at.is_empty() {
+            let found = search::find(&mut reader, &self.start_at)?;
+            match self.report_start(found.map(|p| start + p)) {
+                Some(found) => start = found,
+                None => return Ok(()),
+            }
+            head = &self.start_at;
+        }
+        if self.identify {
+            // look at the first bytes without consuming them
+            let mut first = head.to_vec();
+            first.extend_from_slice(reader.fill_buf()?);
+            self.print_identity(out, &first)?;
+        }
+        let mut buffer = vec![0; self.cols];
+        let mut dump = Dump::new(self, start);
+        dump.start(out)?;
+        if self.follow {
+            catch_interrupt();
+        }
+
+        // read through file
+        let mut n = 0;
+        loop {
+            // readers like pipes may return less than a line at a time
+            n += head.read(&mut buffer[n..])?;
+            n += read_full(&mut reader, &mut buffer[n..])?;
+            // when following, wait for the input to grow to a full line,
+            // unless the limit is reached or the dump is interrupted
+            let more = reader.get_ref().limit() > 0;
+            if n < self.cols && self.follow && more && !INTERRUPTED.load(Ordering::Relaxed) {
+                out.flush()?;
+                std::thread::sleep(FOLLOW_INTERVAL);
+                continue;
+            }
+            if n == 0 && dump.skipped_lines == 0 {
+                break;
+            }
+            if dump.line(out, &buffer, n)? {
+                break;
+            }
+            n = 0;
+        }
+        dump.finish(out)
+    }
+}
+
+// Dump keeps the state of a running dump, lines of bytes are fed to it one
+// at a time by the read loop.
+struct Dump<'a> {
+    dumper: &'a Dumper,
+    fmt: LineFormat<'a>,
+    // raw output formats print the bytes without any markers or squeezing
+    raw: bool,
+    offset: u64,
+    // last_line holds the bytes of the last line printed, for squeezing
+    // lines repeating it
+    last_line: Vec<u8>,
+    skipped_lines: usize,
+    // run is the byte of the run of identical bytes ending the lines emitted
+    // so far, and its length
+    run: Option<(u8, u64)>,
+    // dumped is the number of bytes handed to the dump so far
+    dumped: u64,
+    // end_printed is set once the end offset is printed on a line of its own
+    end_printed: bool,
+    // number of bytes written by raw output formats
+    raw_count: usize,
+    // array holds the bytes of the rust format, the length of the array is
+    // declared before its elements
+    array: Vec<u8>,
+    base64: Base64,
+    crc32: Option<Crc32>,
+    sha256: Option<Sha256>,
+    histogram: Option<Histogram>,
+    // count is the number of occurrences of the byte counted with Count, or
+    // of the pattern with Find
+    count: u64,
+    entropy: Option<Entropy>,
+    strings: Option<Strings>,
+    // decoded holds the bytes dumped with Decode, decoded once all are read
+    decoded: Vec<u8>,
+    // matcher finds the search pattern
+    matcher: Option<Matcher>,
+    matches: Vec<(u64, u64)>,
+    // stop_matcher finds the pattern ending the dump, stopped is set once
+    // it is found
+    stop_matcher: Option<Matcher>,
+    stopped: bool,
+    // lines are kept in pending until the lookahead bytes following them,
+    // as needed for matches and chars ending past the line, are known
+    pending: VecDeque<(Vec<u8>, usize, u64)>,
+    lookahead: usize,
+    context: Context,
+}
+
+// Context keeps track of the lines printed around matches with search_only
+#[derive(Default)]
+struct Context {
+    before: Option<(Line, u64)>,
+    after: usize,
+    printed_end: Option<u64>,
+}
+
+impl<'a> Dump<'a> {
+    fn new(dumper: &'a Dumper, start: u64) -> Dump<'a> {
+        let raw = dumper.plain
+            || matches!(
+                dumper.format,
+                Format::C
+                    | Format::Rust
+                    | Format::Python
+                    | Format::Go
+                    | Format::Base64
+                    | Format::Json
+                    | Format::Csv
+            );
+        // only the hexdump formats can highlight matches
+        let find = dumper.mode == Mode::Find;
+        let matcher = ((!raw || find) && !dumper.search.is_empty())
+            .then(|| Matcher::new(&dumper.search, start));
+        let mut lookahead = matcher.as_ref().map_or(0, |m| m.len() - 1);
+        if !raw && dumper.charset == Charset::Utf8 {
+            // a utf-8 char may continue on the next line
+            lookahead = lookahead.max(3);
+        }
+        let mut fmt = dumper.line_format();
+        fmt.line_start = start;
+        if dumper.relative {
+            fmt.origin = start;
+        }
+        Dump {
+            dumper,
+            raw,
+            offset: start,
+            last_line: Vec::new(),
+            skipped_lines: 0,
+            run: None,
+            dumped: 0,
+            end_printed: false,
+            raw_count: 0,
+            array: Vec::new(),
+            base64: Base64::default(),
+            crc32: (dumper.crc32 || dumper.expect_crc32.is_some()).then(Crc32::default),
+            sha256: (dumper.sha256 || dumper.expect_sha256.is_some()).then(Sha256::default),
+            histogram: (dumper.mode == Mode::Histogram).then(Histogram::default),
+            count: 0,
+            entropy: match dumper.mode {
+                Mode::Entropy(block) => Some(Entropy::new(block, fmt.shown(start))),
+                _ => None,
+            },
+            strings: match dumper.mode {
+                Mode::Strings(min) => Some(Strings::new(min, fmt.shown(start))),
+                _ => None,
+            },
+            decoded: Vec::new(),
+            matcher,
+            matches: Vec::new(),
+            stop_matcher: (!dumper.stop_at.is_empty())
+                .then(|| Matcher::new(&dumper.stop_at, start)),
+            stopped: false,
+            pending: VecDeque::new(),
+            lookahead,
+            context: Context::default(),
+            fmt,
+        }
+    }
+
+    // start prints what goes before the first line
+    fn start<W: Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        if self.dumper.mode != Mode::Dump {
+            return Ok(());
+        }
+        if self.dumper.ruler && !self.raw {
+            self.fmt.print_ruler(out, self.dumper.cols)?;
+        }
+        let seeked = self.dumper.offset.is_some() || self.offset > 0;
+        if seeked && !self.raw && self.dumper.format.markers() {
+            writeln!(out, "{}", self.dumper.seek_marker)?; // indicate not at SOF
+        }
+        match self.dumper.format {
+            Format::C => writeln!(out, "unsigned char {}[] = {{", self.dumper.name),
+            Format::Python => writeln!(out, "{} = (", self.dumper.name),
+            Format::Go => writeln!(out, "var {} = []byte{{", self.dumper.name),
+            Format::Csv if self.dumper.header => writeln!(out, "offset,hex,ascii"),
+            _ => Ok(()),
+        }
+    }
+
+    // line handles the first n bytes of buf as the next line of the dump,
+    // true is returned when the limit is reached and the dump should stop.
+    fn line<W: Write>(&mut self, out: &mut W, buf: &[u8], n: usize) -> std::io::Result<bool> {
+        let limit = self.dumper.limit;
+        self.offset += n as u64;
+        let offset = self.offset;
+        if let Some(crc) = self.crc32.as_mut() {
+            crc.update(&buf[0..n]);
+        }
+        if let Some(sha) = self.sha256.as_mut() {
+            sha.update(&buf[0..n]);
+        }
+        if let Some(matcher) = self.stop_matcher.as_mut() {
+            let stopped = &mut self.stopped;
+            matcher.update(&buf[0..n], |_| *stopped = true);
+        }
+        let before = self.dumped;
+        self.dumped += n as u64;
+        if let Some(total) = self.dumper.progress {
+            if before / PROGRESS_BYTES != self.dumped / PROGRESS_BYTES {
+                print_progress(self.dumped, total);
+            }
+        }
+
+        // scanning modes only look at the bytes, nothing is printed per line
+        if self.dumper.mode != Mode::Dump {
+            if let Some(h) = self.histogram.as_mut() {
+                h.update(&buf[0..n]);
+            }
+            if let Some(e) = self.entropy.as_mut() {
+                e.update(out, &buf[0..n])?;
+            }
+            if let Some(s) = self.strings.as_mut() {
+                s.update(out, &buf[0..n])?;
+            }
+            if self.dumper.mode == Mode::Carve {
+                out.write_all(&buf[0..n])?;
+            }
+            if let Mode::Decode(_) = self.dumper.mode {
+                self.decoded.extend_from_slice(&buf[0..n]);
+            }
+            if let Mode::Count(value) = self.dumper.mode {
+                self.count += buf[0..n].iter().filter(|b| **b == value).count() as u64;
+            }
+            if let (Mode::Find, Some(matcher)) = (self.dumper.mode, self.matcher.as_mut()) {
+                let mut found = Vec::new();
+                matcher.update(&buf[0..n], |start| found.push(start));
+                for start in &found {
+                    writeln!(out, "{:08x}", self.fmt.shown(*start))?;
+                }
+                self.count += found.len() as u64;
+            }
+            return Ok(n == 0 || offset == limit || self.stopped);
+        }
+
+        if self.lookahead == 0 {
+            return Ok(self.emit(out, buf, n, offset, &[])? || self.stopped);
+        }
+        if let Some(matcher) = self.matcher.as_mut() {
+            let len = matcher.len() as u64;
+            let matches = &mut self.matches;
+            matcher.update(&buf[0..n], |start| matches.push((start, start + len)));
+        }
+        if n > 0 {
+            self.pending.push_back((buf.to_vec(), n, offset));
+        }
+
+        // a line is settled once the lookahead bytes following it have been
+        // seen, at EOF all lines are
+        let mut stop = false;
+        while let Some((_, _, end)) = self.pending.front() {
+            if n > 0 && end + self.lookahead as u64 > offset {
+                break;
+            }
+            stop |= self.emit_pending(out)?;
+        }
+        if n == 0 && self.skipped_lines > 0 {
+            stop |= self.emit(out, buf, 0, offset, &[])?;
+        }
+        // lines still pending when stopped are emitted by finish
+        Ok(stop || offset == limit || self.stopped)
+    }
+
+    // emit_pending emits the first of the pending lines, with the bytes of
+    // the lines following it as lookahead.
+    fn emit_pending<W: Write>(&mut self, out: &mut W) -> std::io::Result<bool> {
+        let Some((line, n, end)) = self.pending.pop_front() else {
+            return Ok(false);
+        };
+        let next: Vec<u8> = self
+            .pending
+            .iter()
+            .flat_map(|(b, n, _)| &b[0..*n])
+            .take(self.lookahead)
+            .copied()
+            .collect();
+        let stop = self.emit(out, &line, n, end, &next)?;
+        self.matches.retain(|(_, e)| *e > end);
+        Ok(stop)
+    }
+
+    // emit prints the first n bytes of buf, ending at offset end, as a line of
+    // the dump, next holds the bytes following the line if known. true is
+    // returned when the line reached the limit.
+    fn emit<W: Write>(
+        &mut self,
+        out: &mut W,
+        buf: &[u8],
+        n: usize,
+        end: u64,
+        next: &[u8],
+    ) -> std::io::Result<bool> {
+        let fmt = &self.fmt;
+        let limit = self.dumper.limit;
+
+        if self.raw {
+            let bytes = &buf[0..n];
+            match self.dumper.format {
+                Format::C => print_c(out, bytes, &mut self.raw_count, self.dumper.upper)?,
+                Format::Rust => self.array.extend_from_slice(bytes),
+                Format::Python if n > 0 => {
+                    print_python(out, bytes, self.dumper.escape_all, self.dumper.upper)?;
+                    self.raw_count += n;
+                }
+                Format::Python => {}
+                Format::Go => print_go(out, bytes, &mut self.raw_count, self.dumper.upper)?,
+                Format::Base64 => self.base64.push(out, bytes)?,
+                Format::Json if n > 0 => print_json(out, fmt.shown(end - n as u64), bytes)?,
+                Format::Json => {}
+                Format::Csv if n > 0 => {
+                    print_csv(out, fmt.shown(end - n as u64), bytes, self.dumper.upper)?
+                }
+                Format::Csv => {}
+                _ => print_plain(out, bytes, &mut self.raw_count, self.dumper.upper)?,
+            }
+            return Ok(n == 0 || end == limit);
+        }
+
+        // mark the bytes of the line covered by a match
+        let start = end - n as u64;
+        let marks: Vec<bool> = (start..end)
+            .map(|i| self.matches.iter().any(|(s, e)| (*s..*e).contains(&i)))
+            .collect();
+        let matched = marks.contains(&true);
+
+        // the annotations of the bytes of the line
+        let dumper = self.dumper;
+        let first = dumper.annotations.partition_point(|(o, _)| *o < start);
+        let count = dumper.annotations[first..].partition_point(|(o, _)| *o < end);
+        let notes = &dumper.annotations[first..first + count];
+
+        // skip lines repeating the last line printed, if they are complete
+        // lines and have nothing to highlight or annotate
+        let squeeze =
+            self.dumper.squeeze && !self.dumper.search_only && !matched && notes.is_empty();
+        if squeeze && n == self.dumper.cols && self.last_line == buf[0..n] {
+            self.skipped_lines += 1;
+            self.track_run(&buf[0..n]);
+            return Ok(false);
+        }
+
+        if self.skipped_lines > 0 {
+            // indicate one or more skipped lines
+            match self.fill(&buf[0..n]) {
+                Some((b, len)) => writeln!(
+                    out,
+                    "{} {} x {}",
+                    self.dumper.squeeze_marker,
+                    word_as_hex(&[b], &[], &self.fmt),
+                    len
+                )?,
+                None => self
+                    .dumper
+                    .print_skipped(out, self.skipped_lines, self.repeated())?,
+            }
+            self.skipped_lines = 0;
+        }
+        self.track_run(&buf[0..n]);
+        let fmt = &self.fmt;
+
+        let line = build_line(end, buf, n, fmt, &marks, next);
+        if self.dumper.search_only {
+            self.print_context(out, line, matched, end)?;
+        } else {
+            for (offset, name) in notes {
+                writeln!(out, "; 0x{:x} = {}", offset, name)?;
+            }
+            line.print(out, fmt)?;
+        }
+
+        self.last_line.clear();
+        self.last_line.extend_from_slice(&buf[0..n]);
+        self.end_printed = n == 0;
+
+        if end == limit && !self.dumper.format.markers() {
+            return Ok(true);
+        }
+        if end == limit {
+            writeln!(out, "{}", self.dumper.seek_marker)?; // indicate end before EOF
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    // hole stands in for len bytes of a hole in the input, printing the
+    // lines pending and the squeeze marker with the size of the hole
+    fn hole<W: Write>(&mut self, out: &mut W, len: u64) -> std::io::Result<()> {
+        while !self.pending.is_empty() {
+            self.emit_pending(out)?;
+        }
+        // skipped lines of zeros before the hole are part of it, any other
+        // lines skipped are ended by their offset
+        let mut size = len;
+        if self.skipped_lines > 0 && self.repeated() == Some(0) {
+            size += self.skipped_lines as u64 * self.dumper.cols as u64;
+            self.skipped_lines = 0;
+        } else if self.skipped_lines > 0 {
+            self.emit(out, &[], 0, self.offset, &[])?;
+        }
+        writeln!(
+            out,
+            "{} (hole: 0x{:x} bytes)",
+            self.dumper.squeeze_marker, size
+        )?;
+        self.offset += len;
+        // the line following the hole is never squeezed
+        self.last_line.clear();
+        self.run = None;
+        Ok(())
+    }
+
+    // track_run updates the run of identical bytes with the bytes of the
+    // next line
+    fn track_run(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.run = match self.run {
+                Some((value, len)) if value == *b => Some((value, len + 1)),
+                _ => Some((*b, 1)),
+            };
+        }
+    }
+
+    // repeated returns the byte filling the last line printed, if it
+    // repeats a single one
+    fn repeated(&self) -> Option<u8> {
+        let first = *self.last_line.first()?;
+        self.last_line.iter().all(|b| *b == first).then_some(first)
+    }
+
+    // fill returns the byte repeated by the skipped lines, and the length of
+    // the run it fills up to the first byte of next that differs, if rle is
+    // enabled and the skipped lines repeat a single byte.
+    fn fill(&self, next: &[u8]) -> Option<(u8, u64)> {
+        let (value, len) = self.run.filter(|_| self.dumper.rle)?;
+        if self.repeated() != Some(value) {
+            return None;
+        }
+        let lead = next.iter().take_while(|b| **b == value).count();
+        Some((value, len + lead as u64))
+    }
+
+    // print_context prints line if it matched, or if it is within
+    // SEARCH_CONTEXT lines of a line that did, separating groups of lines
+    // that are not adjacent with "--".
+    fn print_context<W: Write>(
+        &mut self,
+        out: &mut W,
+        line: Line,
+        matched: bool,
+        end: u64,
+    ) -> std::io::Result<()> {
+        let ctx = &mut self.context;
+        if !matched && ctx.after == 0 {
+            // keep the line, in case the next one matches
+            ctx.before = (SEARCH_CONTEXT > 0).then_some((line, end));
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        if let Some(before) = ctx.before.take() {
+            lines.push(before);
+        }
+        lines.push((line, end));
+        for (line, end) in lines {
+            if ctx.printed_end.is_some_and(|e| e != line.start_offset) {
+                writeln!(out, "--")?;
+            }
+            line.print(out, &self.fmt)?;
+            ctx.printed_end = Some(end);
+        }
+        ctx.after = if matched {
+            SEARCH_CONTEXT
+        } else {
+            ctx.after - 1
+        };
+        Ok(())
+    }
+
+    // finish prints what goes after the last line
+    fn finish<W: Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        while !self.pending.is_empty() {
+            if self.emit_pending(out)? {
+                self.pending.clear();
+            }
+        }
+        // od always ends with the end offset
+        if self.skipped_lines > 0 || (self.dumper.format == Format::Od && !self.end_printed) {
+            self.emit(out, &[], 0, self.offset, &[])?;
+        }
+        // indicate stopping before EOF, like stopping at the limit does
+        let markers = !self.raw && self.dumper.format.markers() && self.dumper.mode == Mode::Dump;
+        if self.stopped && markers && self.offset != self.dumper.limit {
+            writeln!(out, "{}", self.dumper.seek_marker)?;
+        }
+        match self.dumper.format {
+            _ if self.dumper.mode != Mode::Dump => {
+                if let Some(h) = &self.histogram {
+                    h.print(out, self.dumper.width)?;
+                }
+                if let Some(e) = self.entropy.as_mut() {
+                    e.finish(out)?;
+                }
+                if let Some(s) = self.strings.as_mut() {
+                    s.finish(out)?;
+                }
+                match self.dumper.mode {
+                    Mode::Count(_) => writeln!(out, "{}", self.count)?,
+                    Mode::Find if self.count == 1 => eprintln!("1 match"),
+                    Mode::Find => eprintln!("{} matches", self.count),
+                    Mode::Carve => eprintln!("carved 0x{:x} ({}) bytes", self.dumped, self.dumped),
+                    Mode::Decode(interpret) => writeln!(
+                        out,
+                        "{}",
+                        interpret.decode_region(&self.decoded, self.dumper.endian)?
+                    )?,
+                    _ => {}
+                }
+            }
+            Format::C => {
+                if self.raw_count > 0 {
+                    writeln!(out)?;
+                }
+                writeln!(out, "}};")?;
+                writeln!(
+                    out,
+                    "unsigned int {}_len = {};",
+                    self.dumper.name, self.raw_count
+                )?;
+            }
+            Format::Rust => print_rust(out, &self.dumper.name, &self.array, self.dumper.upper)?,
+            Format::Python => {
+                if self.raw_count == 0 {
+                    // a pair of parentheses alone is an empty tuple
+                    writeln!(out, "    b''")?;
+                }
+                writeln!(out, ")")?;
+            }
+            Format::Go => {
+                if !self.raw_count.is_multiple_of(C_LINE_BYTES) {
+                    writeln!(out)?;
+                }
+                writeln!(out, "}}")?;
+            }
+            Format::Base64 => self.base64.finish(out)?,
+            Format::Json | Format::Csv => {}
+            Format::Hexdump
+            | Format::Octal
+            | Format::Binary
+            | Format::Decimal
+            | Format::Od
+            | Format::Xxd => {
+                if self.raw && !self.raw_count.is_multiple_of(PLAIN_LINE_BYTES) {
+                    writeln!(out)?;
+                }
+            }
+        }
+        // the carved bytes are kept apart from the checksums
+        let mut stderr = std::io::stderr();
+        let sums: &mut dyn Write = match self.dumper.mode {
+            Mode::Carve => &mut stderr,
+            _ => out,
+        };
+        let mut mismatch = false;
+        if let Some(crc) = &self.crc32 {
+            write!(sums, "crc32: {:08x}", crc.value())?;
+            match self.dumper.expect_crc32 {
+                Some(expected) if expected == crc.value() => write!(sums, " OK")?,
+                Some(expected) => {
+                    write!(sums, " MISMATCH, expected {:08x}", expected)?;
+                    mismatch = true;
+                }
+                None => {}
+            }
+            writeln!(sums)?;
+        }
+        if let Some(sha) = &self.sha256 {
+            let hex =
+                |digest: &[u8]| -> String { digest.iter().map(|b| format!("{:02x}", b)).collect() };
+            let digest = sha.digest();
+            write!(sums, "sha256: {}", hex(&digest))?;
+            match self.dumper.expect_sha256 {
+                Some(expected) if expected == digest => write!(sums, " OK")?,
+                Some(expected) => {
+                    write!(sums, " MISMATCH, expected {}", hex(&expected))?;
+                    mismatch = true;
+                }
+                None => {}
+            }
+            writeln!(sums)?;
+        }
+        if self.dumper.progress.is_some() {
+            eprint!("\r\x1b[K"); // erase the progress line
+        }
+        out.flush()?;
+        if self.dumper.summary {
+            let end = self.dumper.base_offset + self.offset;
+            eprintln!(
+                "dumped 0x{:x} ({}) bytes from offset 0x{:x} to 0x{:x}",
+                self.dumped,
+                self.dumped,
+                end - self.dumped,
+                end
+            );
+        }
+        if mismatch {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "checksum mismatch",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// print_progress rewrites the progress line on stderr with the number of
+// bytes dumped, as a percentage of total if known.
+fn print_progress(dumped: u64, total: u64) {
+    if total > 0 {
+        let percent = (dumped as f64 * 100.0 / total as f64).min(100.0);
+        eprint!("\r{:5.1}%", percent);
+    } else {
+        eprint!("\r0x{:x} bytes", dumped);
+    }
+}
+
+// print_plain prints bytes as continuous hex, breaking the line every
+// PLAIN_LINE_BYTES bytes. count keeps track of the number of bytes already
+// printed between calls.
+fn print_plain<W: Write>(
+    out: &mut W,
+    bytes: &[u8],
+    count: &mut usize,
+    upper: bool,
+) -> std::io::Result<()> {
+    for b in bytes {
+        if upper {
+            write!(out, "{:02X}", b)?;
+        } else {
+            write!(out, "{:02x}", b)?;
+        }
+        *count += 1;
+        if count.is_multiple_of(PLAIN_LINE_BYTES) {
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+// print_c prints bytes as the comma separated elements of a C array,
+// C_LINE_BYTES to a line. count keeps track of the number of bytes already
+// printed between calls.
+fn print_c<W: Write>(
+    out: &mut W,
+    bytes: &[u8],
+    count: &mut usize,
+    upper: bool,
+) -> std::io::Result<()> {
+    for b in bytes {
+        if *count == 0 {
+            write!(out, "  ")?;
+        } else if count.is_multiple_of(C_LINE_BYTES) {
+            write!(out, ",\n  ")?;
+        } else {
+            write!(out, ", ")?;
+        }
+        if upper {
+            write!(out, "0x{:02X}", b)?;
+        } else {
+            write!(out, "0x{:02x}", b)?;
+        }
+        *count += 1;
+    }
+    Ok(())
+}
+
+// print_rust prints bytes as a rust constant array named name, C_LINE_BYTES
+// to a line
+fn print_rust<W: Write>(out: &mut W, name: &str, bytes: &[u8], upper: bool) -> std::io::Result<()> {
+    if bytes.is_empty() {
+        return writeln!(out, "const {}: [u8; 0] = [];", name);
+    }
+    writeln!(out, "const {}: [u8; {}] = [", name, bytes.len())?;
+    for line in bytes.chunks(C_LINE_BYTES) {
+        let elements: Vec<String> = line
+            .iter()
+            .map(|b| match upper {
+                true => format!("0x{:02X},", b),
+                false => format!("0x{:02x},", b),
+            })
+            .collect();
+        writeln!(out, "    {}", elements.join(" "))?;
+    }
+    writeln!(out, "];")
+}
+
+// print_python prints a line of bytes as a python bytes literal on a line of
+// its own, with printable ascii as is unless escape_all is set
+fn print_python<W: Write>(
+    out: &mut W,
+    bytes: &[u8],
+    escape_all: bool,
+    upper: bool,
+) -> std::io::Result<()> {
+    let mut literal = String::new();
+    for b in bytes {
+        match (escape_all, *b) {
+            (false, b'\\') => literal += "\\\\",
+            (false, b'\'') => literal += "\\'",
+            (false, b'\n') => literal += "\\n",
+            (false, b'\r') => literal += "\\r",
+            (false, b'\t') => literal += "\\t",
+            (false, b) if is_printable(b) => literal.push(b as char),
+            (_, b) if upper => literal += &format!("\\x{:02X}", b),
+            (_, b) => literal += &format!("\\x{:02x}", b),
+        }
+    }
+    writeln!(out, "    b'{}'", literal)
+}
+
+// print_go prints bytes as the elements of a go byte slice, each followed
+// by a comma as gofmt has it, C_LINE_BYTES to a line. count keeps track of
+// the number of bytes already printed between calls.
+fn print_go<W: Write>(
+    out: &mut W,
+    bytes: &[u8],
+    count: &mut usize,
+    upper: bool,
+) -> std::io::Result<()> {
+    for b in bytes {
+        if count.is_multiple_of(C_LINE_BYTES) {
+            write!(out, "\t")?;
+        } else {
+            write!(out, " ")?;
+        }
+        if upper {
+            write!(out, "0x{:02X},", b)?;
+        } else {
+            write!(out, "0x{:02x},", b)?;
+        }
+        *count += 1;
+        if count.is_multiple_of(C_LINE_BYTES) {
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+// print_json prints a line of bytes as a JSON object on a single line
+fn print_json<W: Write>(out: &mut W, start_offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+    let hex: Vec<String> = bytes.iter().map(|b| b.to_string()).collect();
+    let mut ascii = String::new();
+    for b in bytes {
+        match printable(*b) {
+            '"' => ascii += "\\\"",
+            '\\' => ascii += "\\\\",
+            c => ascii.push(c),
+        }
+    }
+    writeln!(
+        out,
+        "{{\"offset\":{},\"hex\":[{}],\"ascii\":\"{}\"}}",
+        start_offset,
+        hex.join(","),
+        ascii
+    )
+}
+
+// print_csv prints a line of bytes as a csv row, the ascii field is always
+// quoted as it may contain both commas and quotes.
+fn print_csv<W: Write>(
+    out: &mut W,
+    start_offset: u64,
+    bytes: &[u8],
+    upper: bool,
+) -> std::io::Result<()> {
+    let mut hex = String::new();
+    let mut ascii = String::new();
+    for b in bytes {
+        if upper {
+            hex += &format!("{:02X}", b);
+        } else {
+            hex += &format!("{:02x}", b);
+        }
+        match printable(*b) {
+            '"' => ascii += "\"\"",
+            c => ascii.push(c),
+        }
+    }
+    writeln!(out, "{},{},\"{}\"", start_offset, hex, ascii)
+}
+
+// Base64 is a streaming base64 encoder printing its output wrapped
+// at BASE64_LINE_CHARS, bytes not yet making up a full 3 byte group are
+// kept until more bytes are pushed or the encoder is finished.
+#[derive(Default)]
+struct Base64 {
+    pending: Vec<u8>,
+    col: usize,
+}
+
+impl Base64 {
+    fn push<W: Write>(&mut self, out: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+        self.pending.extend_from_slice(bytes);
+        let full = self.pending.len() - self.pending.len() % 3;
+        let groups: Vec<u8> = self.pending.drain(0..full).collect();
+        for group in groups.chunks(3) {
+            self.encode(out, group)?;
+        }
+        Ok(())
+    }
+
+    // finish encodes any pending bytes with padding and ends the last line
+    fn finish<W: Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        let rest = std::mem::take(&mut self.pending);
+        if !rest.is_empty() {
+            self.encode(out, &rest)?;
+        }
+        if self.col > 0 {
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    // encode prints a group of one to three bytes as four base64 characters
+    fn encode<W: Write>(&mut self, out: &mut W, group: &[u8]) -> std::io::Result<()> {
+        let mut bits: u32 = 0;
+        for (i, b) in group.iter().enumerate() {
+            bits |= (*b as u32) << (16 - 8 * i);
+        }
+        for i in 0..4 {
+            let c = if i <= group.len() {
+                BASE64_ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char
+            } else {
+                '='
+            };
+            write!(out, "{}", c)?;
+            self.col += 1;
+            if self.col == BASE64_LINE_CHARS {
+                writeln!(out)?;
+                self.col = 0;
+            }
+        }
+        Ok(())
+    }
+}
+
+// c_identifier turns a filename into a valid C identifier by replacing
+// anything but ascii letters and digits with '_', stdin is named "data".
+pub fn c_identifier(filename: &str) -> String {
+    if filename == "-" {
+        return "data".to_string();
+    }
+    let mut id: String = filename
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) {
+        id.insert(0, '_');
+    }
+    id
+}
+
+// line_from_buffer will iterate over the the first "n" bytes of the buffer
+// in "word_sized" chunks and add them to both the hexadecimal and the ascii output-strings.
+// The ascii output-string is only built if the format asks for it.
+// Bytes with their mark set are highlighted, marks may be empty for none.
+// next holds the bytes following the line, for chars continuing past it.
+pub fn build_line(
+    end_offset: u64,
+    buf: &[u8],
+    n: usize,
+    fmt: &LineFormat,
+    marks: &[bool],
+    next: &[u8],
+) -> Line {
+    let mut hex: String = String::new();
+    let mut hex_width: usize = 0;
+    let mut ascii: Option<String> = fmt.dumper.ascii.then(String::new);
+    let mut values: Option<String> = fmt.dumper.interpret.map(|_| String::new());
+    let mut marks = marks.to_vec();
+    marks.resize(n, false);
+    let glyphs: Vec<char> = match fmt.dumper.charset {
+        Charset::Utf8 => utf8_glyphs(&buf[0..n], next),
+        charset => buf[0..n].iter().map(|b| charset.glyph(*b)).collect(),
+    }
+    .into_iter()
+    .map(|g| g.unwrap_or(fmt.dumper.unprintable))
+    .collect();
+    for (i, ((word, word_marks), word_glyphs)) in buf[0..n]
+        .chunks(fmt.dumper.word_size)
+        .zip(marks.chunks(fmt.dumper.word_size))
+        .zip(glyphs.chunks(fmt.dumper.word_size))
+        .enumerate()
+    {
+        // separate groups, there is no separator after the last one. With
+        // nibbles bytes are separated by one more space than their halves
+        let group_start = (i * fmt.dumper.word_size).is_multiple_of(fmt.group);
+        if i > 0 && fmt.dumper.nibbles {
+            hex.push(' ');
+            hex_width += 1;
+        }
+        if i > 0 && group_start {
+            hex += &fmt.dumper.group_sep;
+            hex_width += fmt.dumper.group_sep.chars().count();
+        } else if i > 0 && fmt.dumper.nibbles {
+            hex.push(' ');
+            hex_width += 1;
+        }
+        let split = fmt.split_before(i * fmt.dumper.word_size);
+        if split {
+            hex.push('|');
+            hex += &fmt.dumper.group_sep;
+            hex_width += 1 + fmt.dumper.group_sep.chars().count();
+        }
+        hex += &word_as_hex(word, word_marks, fmt);
+        hex_width += fmt.group_width(word.len());
+        if !fmt.escapes() {
+            // the brackets around highlighted and marked bytes
+            hex_width += 2 * word
+                .iter()
+                .zip(word_marks)
+                .filter(|(b, marked)| fmt.dumper.highlight == Some(**b) || **marked)
+                .count();
+        }
+        if let Some(a) = ascii.as_mut() {
+            if fmt.dumper.ctrl_names && i > 0 {
+                a.push(' ');
+            }
+            if split {
+                a.push('|');
+            }
+            *a += &word_as_ascii(word, word_glyphs, word_marks, fmt);
+        }
+        if let (Some(v), Some(interpret)) = (values.as_mut(), fmt.dumper.interpret) {
+            // a short last word has no value
+            if word.len() == interpret.size() {
+                let w = interpret.width();
+                if i > 0 {
+                    *v += " ";
+                }
+                *v += &format!("{:>w$}", interpret.decode(word, fmt.dumper.endian));
+            }
+        }
+    }
+    if fmt.dumper.entropy_color {
+        hex = format!("\x1b[38;5;{}m{}\x1b[0m", entropy_tint(&buf[0..n]), hex);
+    }
+    Line {
+        ascii,
+        values,
+        crc: fmt
+            .dumper
+            .line_crc
+            .map(|c| c.checksum(&buf[0..n], fmt.dumper.upper)),
+        strings: line_strings(&buf[0..n], end_offset - n as u64, fmt),
+        hex,
+        hex_width,
+        start_offset: end_offset - n as u64,
+    }
+}
+
+// reverse parses lines of a dump in the format produced by rxdump, or by
+// xxd, and writes the decoded bytes to out, positioned at the offset given on
+// each line.
+// A gap following the squeeze marker, alone or followed by the size of the
+// squeezed lines, is filled by repeating the line before it, any other gap
+// between lines, or hole following the marker, is filled with zeros. Lines of
+// the seek marker are ignored.
+pub fn reverse<R: BufRead>(
+    input: R,
+    out: &mut Output,
+    squeeze_marker: &str,
+    seek_marker: &str,
+) -> std::io::Result<()> {
+    let mut pos: u64 = 0;
+    let mut last: Vec<u8> = Vec::new();
+    let mut squeezed = false;
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        if line.is_empty() || line == seek_marker {
+            continue;
+        }
+        let marker = line
+            .strip_prefix(squeeze_marker)
+            .filter(|rest| rest.is_empty() || rest.starts_with(' '));
+        if let Some(len) = marker.and_then(hole_size) {
+            out.skip_to(pos, pos + len)?;
+            pos += len;
+            squeezed = false;
+            continue;
+        }
+        if marker.is_some() {
+            squeezed = true;
+            continue;
+        }
+        let invalid = |what: &str| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("line {}: {}", i + 1, what),
+            )
+        };
+        // a line of just an offset marks the end of squeezed lines
+        let (offset_str, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let hex = match offset_str.strip_suffix(':') {
+            // xxd separates the ascii column by two spaces, without delimiters
+            Some(_) => rest.split("  ").next(),
+            // the ascii column starts with a '|' following the padding
+            None => rest.split("  |").next(),
+        }
+        .unwrap_or("")
+        // the bars splitting the hex column
+        .replace('|', "");
+        let offset_str = offset_str.trim_end_matches(':');
+        let offset_str = offset_str.strip_prefix("0x").unwrap_or(offset_str);
+        let offset = u64::from_str_radix(offset_str, 16).map_err(|_| invalid("invalid offset"))?;
+        let bytes = decode_hex(&hex).ok_or_else(|| invalid("invalid hex"))?;
+        if squeezed && !last.is_empty() && offset > pos {
+            // the squeezed lines repeat the last line
+            while pos + last.len() as u64 <= offset {
+                out.write_all(&last)?;
+                pos += last.len() as u64;
+            }
+        }
+        squeezed = false;
+        if offset != pos {
+            out.skip_to(pos, offset)?;
+            pos = offset;
+        }
+        out.write_all(&bytes)?;
+        pos += bytes.len() as u64;
+        if !bytes.is_empty() {
+            last = bytes;
+        }
+    }
+    out.flush()
+}
+
+// hole_size returns the size of the hole following a squeeze marker, the
+// rest of a line like "* (hole: 0x1000 bytes)"
+fn hole_size(rest: &str) -> Option<u64> {
+    let size = rest.trim_start().strip_prefix("(hole: 0x")?;
+    u64::from_str_radix(size.strip_suffix(" bytes)")?, 16).ok()
+}
+
+// decode_hex parses a string of hexadecimal digit pairs into bytes, any
+// whitespace in the string is ignored. None is returned if the string
+// contains anything but hex digits or has an odd number of digits.
+pub fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+// as_u64 parses a string to a u64, if the string is prefixed with '0x' the string
+// will be parsed as hexadecimal, if not it will be parsed as decimal. The value
+// may be followed by a K, M or G suffix (powers of 1000) or Ki, Mi or Gi
+// (powers of 1024).
+pub fn as_u64(s: &str) -> Result<u64, String> {
+    let hex = s.starts_with("0x");
+    let digits_end = s
+        .char_indices()
+        .skip(if hex { 2 } else { 0 })
+        .find(|(_, c)| !(hex && c.is_ascii_hexdigit() || c.is_ascii_digit()))
+        .map_or(s.len(), |(i, _)| i);
+    let (number, suffix) = s.split_at(digits_end);
+    let multiplier: u64 = match suffix {
+        "" => 1,
+        "K" => 1000,
+        "M" => 1000 * 1000,
+        "G" => 1000 * 1000 * 1000,
+        "Ki" => 1 << 10,
+        "Mi" => 1 << 20,
+        "Gi" => 1 << 30,
+        _ => {
+            return Err(format!(
+                "unknown suffix '{}', expected K, M, G, Ki, Mi or Gi",
+                suffix
+            ))
+        }
+    };
+    let value = if hex {
+        let h = number.trim_start_matches("0x");
+        u64::from_str_radix(h, 16)
+    } else {
+        number.parse()
+    }
+    .map_err(|e| e.to_string())?;
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| "number too large to fit in target type".to_string())
+}
+
+// human_size renders a number of bytes in the largest binary unit it makes
+// at least one of, with a single decimal, like "1.5 MiB"
+pub fn human_size(n: u64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut size = n as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+// all_zero will return true if all bytes in a byte array is zero
+pub fn all_zero(line: &[u8]) -> bool {
+    !line.iter().any(|&x| x != 0)
+}
+
+// word_as_hex converts an array of bytes to a hex string, it will pad
+// the hexvalue of each byte witn '0', using uppercase digits if upper is set.
+// With an octal, binary or decimal radix each byte is rendered as three
+// octal digits, eight binary digits or a decimal value padded to three
+// columns instead. With nibbles the two halves of a hex or binary byte are
+// separated by a space, and the bytes themselves by two.
+// With little endian the bytes of the word are shown in reverse order.
+pub fn word_as_hex(word: &[u8], marks: &[bool], fmt: &LineFormat) -> String {
+    let mut wds: String = String::new();
+    let mut bytes: Vec<(u8, bool)> = word
+        .iter()
+        .copied()
+        .zip(marks.iter().copied().chain(std::iter::repeat(false)))
+        .collect();
+    if fmt.dumper.endian == Endian::Little {
+        bytes.reverse();
+    }
+    for (byte, marked) in bytes {
+        let mut letter = match fmt.radix {
+            Radix::Hex if fmt.dumper.upper => format!("{:02X}", byte),
+            Radix::Hex => format!("{:02x}", byte),
+            Radix::Octal => format!("{:03o}", byte),
+            Radix::Binary => format!("{:08b}", byte),
+            Radix::Decimal => format!("{:>3}", byte),
+        };
+        if fmt.dumper.nibbles {
+            if !wds.is_empty() {
+                wds.push_str("  ");
+            }
+            letter.insert(letter.len() / 2, ' ');
+        }
+        wds += &colorize(&letter, byte, marked, fmt);
+    }
+    wds
+}
+
+// word_as_ascii convets an array of bytes to a printable string, glyphs
+// holding the char to show for each byte as given by the charset. With
+// ctrl_names each byte is a right justified cell of CTRL_CELL columns,
+// separated by a space, holding the mnemonic of control chars.
+pub fn word_as_ascii(word: &[u8], glyphs: &[char], marks: &[bool], fmt: &LineFormat) -> String {
+    let mut a: String = String::new();
+    for (i, b) in word.iter().enumerate() {
+        let marked = marks.get(i).copied().unwrap_or(false);
+        let glyph = glyphs.get(i).copied().unwrap_or('.');
+        if fmt.dumper.ctrl_names {
+            if i > 0 {
+                a.push(' ');
+            }
+            let cell = match fmt.dumper.charset.control_name(*b) {
+                Some(name) => format!("{:>w$}", name, w = CTRL_CELL),
+                None => format!("{:>w$}", glyph, w = CTRL_CELL),
+            };
+            a += &colorize(&cell, *b, marked, fmt);
+        } else {
+            a += &colorize(&glyph.to_string(), *b, marked, fmt);
+        }
+    }
+    a
+}
+
+// line_strings notes the runs of at least inline_strings printable
+// bytes of bytes, starting at offset, like 0x20 "hello", 0x30 "world".
+// Runs continuing on the next line are cut at the end of the line.
+fn line_strings(bytes: &[u8], offset: u64, fmt: &LineFormat) -> Option<String> {
+    if fmt.dumper.inline_strings == 0 {
+        return None;
+    }
+    let mut found = Vec::new();
+    let mut start = 0;
+    for (i, b) in bytes.iter().chain([&0]).enumerate() {
+        if is_printable(*b) {
+            continue;
+        }
+        if i - start >= fmt.dumper.inline_strings {
+            let run: String = bytes[start..i].iter().map(|b| *b as char).collect();
+            found.push(format!(
+                "0x{:x} {:?}",
+                fmt.shown(offset + start as u64),
+                run
+            ));
+        }
+        start = i + 1;
+    }
+    (!found.is_empty()).then(|| found.join(", "))
+}
+
+// is_printable returns true if b is a printable ascii char
+pub fn is_printable(b: u8) -> bool {
+    (0x20..0x7f).contains(&b)
+}
+
+// printable returns the ascii char of b if it is printable, otherwise '.'
+pub fn printable(b: u8) -> char {
+    if is_printable(b) {
+        b as char
+    } else {
+        '.'
+    }
+}
+
+// entropy_tint returns the 256 color palette index, from blue to red, of
+// the entropy of bytes relative to the most a run of its length can have
+fn entropy_tint(bytes: &[u8]) -> u8 {
+    let mut counts = [0; 256];
+    for b in bytes {
+        counts[*b as usize] += 1;
+    }
+    let max = (bytes.len().min(256) as f64).log2();
+    let level = match max > 0.0 {
+        true => (shannon(&counts) / max * 5.0).round() as u8,
+        false => 0,
+    };
+    // red goes up as blue goes down in the 6x6x6 color cube
+    16 + 36 * level + (5 - level)
+}
+
+// colorize wraps s in the ANSI color of the class of byte b, in inverse
+// video if marked or highlighted, if the format has color enabled. With
+// dim_zeros only null bytes are colored, dimmed. Without either a marked
+// or highlighted s is put between brackets, otherwise s is returned as is.
+fn colorize(s: &str, b: u8, marked: bool, fmt: &LineFormat) -> String {
+    let highlighted = fmt.dumper.highlight == Some(b);
+    if !fmt.escapes() {
+        return if highlighted || marked {
+            format!("[{}]", s)
+        } else {
+            s.to_string()
+        };
+    }
+    let marked = marked || highlighted;
+    if fmt.dumper.entropy_color {
+        // keep the tint of the line, only toggling inverse video
+        return if marked {
+            format!("\x1b[7m{}\x1b[27m", s)
+        } else {
+            s.to_string()
+        };
+    }
+    if !fmt.dumper.color && b != 0 && !marked {
+        return s.to_string();
+    }
+    let code = match b {
+        _ if !fmt.dumper.color => {
+            if b == 0 {
+                "2" // dim
+            } else {
+                "39" // default color
+            }
+        }
+        0x00 => "90",        // null, gray
+        0x09..=0x0d => "33", // whitespace control chars, yellow
+        0x20..=0x7e => "32", // printable, green
+        _ => "31",           // everything else, red
+    };
+    if marked {
+        format!("\x1b[{};7m{}\x1b[0m", code, s)
+    } else {
+        format!("\x1b[{}m{}\x1b[0m", code, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // dump returns the dump of bytes by dumper, read from memory
+    fn dump(dumper: &Dumper, bytes: &[u8]) -> String {
+        let mut out = Vec::new();
+        dumper.dump(Cursor::new(bytes.to_vec()), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    // reversed returns the bytes reversed from text, a dump using the given
+    // markers, through a temporary file named after the test
+    fn reversed(text: &str, name: &str, squeeze_marker: &str, seek_marker: &str) -> Vec<u8> {
+        let path = std::env::temp_dir().join(format!("rxdump-{}-{}", name, std::process::id()));
+        let mut out = Output::File(File::create(&path).unwrap());
+        reverse(text.as_bytes(), &mut out, squeeze_marker, seek_marker).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn reverse_custom_markers() {
+        let mut bytes = vec![0; 64];
+        bytes.extend_from_slice(b"end");
+        let dumper = Dumper {
+            squeeze_marker: "SQ".to_string(),
+            seek_marker: "SK".to_string(),
+            offset: Some(16),
+            ..Default::default()
+        };
+        let text = dump(&dumper, &bytes);
+        assert!(text.starts_with("SK\n"));
+        assert_eq!(reversed(&text, "markers", "SQ", "SK"), bytes);
+    }
+
+    // hex returns the hex column of a line of the first n bytes of buf
+    fn hex(word_size: usize, buf: &[u8], n: usize) -> String {
+        let dumper = Dumper {
+            word_size,
+            ..Default::default()
+        };
+        build_line(n as u64, buf, n, &dumper.line_format(), &[], &[]).hex
+    }
+
+    #[test]
+    fn hex_without_trailing_separator() {
+        let buf: Vec<u8> = (0..16).collect();
+        assert_eq!(
+            hex(1, &buf, 16),
+            "00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f"
+        );
+        assert_eq!(hex(1, &buf, 3), "00 01 02");
+    }
+
+    #[test]
+    fn hex_word_sizes() {
+        let buf: Vec<u8> = (0..16).collect();
+        let cases = [
+            (
+                1,
+                "00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f",
+                "00 01 02 03 04 05 06 07 08 09 0a",
+            ),
+            (
+                2,
+                "0001 0203 0405 0607 0809 0a0b 0c0d 0e0f",
+                "0001 0203 0405 0607 0809 0a",
+            ),
+            (
+                4,
+                "00010203 04050607 08090a0b 0c0d0e0f",
+                "00010203 04050607 08090a",
+            ),
+            (
+                8,
+                "0001020304050607 08090a0b0c0d0e0f",
+                "0001020304050607 08090a",
+            ),
+        ];
+        for (word_size, full, short) in cases {
+            assert_eq!(hex(word_size, &buf, 16), full, "word size {}", word_size);
+            assert_eq!(hex(word_size, &buf, 11), short, "word size {}", word_size);
+        }
+    }
+
+    #[test]
+    fn trailing_zero_lines() {
+        let mut bytes = b"trailing zeros..".to_vec();
+        bytes.resize(64, 0);
+        let text = dump(&Dumper::default(), &bytes);
+        assert_eq!(
+            text,
+            "00000000  74 72 61 69 6c 69 6e 67 20 7a 65 72 6f 73 2e 2e  |trailing zeros..|\n\
+             00000010  00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00  |................|\n\
+             *\n\
+             00000040\n"
+        );
+        assert_eq!(reversed(&text, "trailing", "*", "**"), bytes);
+    }
+
+    #[test]
+    fn word_wider_than_default_line() {
+        let dumper = Dumper {
+            word_size: 32,
+            cols: 32,
+            ..Default::default()
+        };
+        let bytes: Vec<u8> = (0x40..0x60).collect();
+        assert_eq!(
+            dump(&dumper, &bytes),
+            "00000000  404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f  \
+             |@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_|\n"
+        );
+    }
+
+    #[test]
+    fn partial_zero_line() {
+        let mut bytes = b"not zero at all!".to_vec();
+        bytes.resize(21, 0);
+        assert_eq!(
+            dump(&Dumper::default(), &bytes),
+            "00000000  6e 6f 74 20 7a 65 72 6f 20 61 74 20 61 6c 6c 21  |not zero at all!|\n\
+             00000010  00 00 00 00 00                                   |.....|\n"
+        );
+    }
+
+    #[test]
+    fn offset_past_skip_overflow() {
+        let dumper = Dumper {
+            skip: u64::MAX,
+            offset: Some(5),
+            ..Default::default()
+        };
+        let err = dumper
+            .dump(Cursor::new(vec![0; 16]), &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    // dump_stream returns the dump of bytes by dumper, read as a stream, and
+    // the number of bytes read from it
+    fn dump_stream(dumper: &Dumper, bytes: &[u8]) -> (String, u64) {
+        let mut reader = Cursor::new(bytes.to_vec());
+        let mut out = Vec::new();
+        dumper.dump_unseekable(&mut reader, &mut out).unwrap();
+        (String::from_utf8(out).unwrap(), reader.position())
+    }
+
+    // ByteReader reads a single byte per call, like a slow pipe
+    struct ByteReader(Cursor<Vec<u8>>);
+
+    impl Read for ByteReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn stream_short_reads() {
+        let bytes: Vec<u8> = (0x30..0x54).collect();
+        let mut out = Vec::new();
+        Dumper::default()
+            .dump_unseekable(ByteReader(Cursor::new(bytes)), &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "00000000  30 31 32 33 34 35 36 37 38 39 3a 3b 3c 3d 3e 3f  |0123456789:;<=>?|\n\
+             00000010  40 41 42 43 44 45 46 47 48 49 4a 4b 4c 4d 4e 4f  |@ABCDEFGHIJKLMNO|\n\
+             00000020  50 51 52 53                                      |PQRS|\n"
+        );
+    }
+
+    #[test]
+    fn stream_limit() {
+        let bytes: Vec<u8> = (0x30..0x70).collect();
+        let dumper = Dumper {
+            limit: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump_stream(&dumper, &bytes),
+            (
+                "00000000  30 31 32 33 34 35 36 37 38 39 3a 3b 3c 3d 3e 3f  |0123456789:;<=>?|\n\
+                 00000010  40 41 42 43                                      |@ABC|\n\
+                 **\n"
+                    .to_string(),
+                20
+            )
+        );
+    }
+
+    #[test]
+    fn stream_start_past_limit() {
+        let bytes: Vec<u8> = (0x30..0x70).collect();
+        let dumper = Dumper {
+            offset: Some(32),
+            limit: 20,
+            ..Default::default()
+        };
+        // nothing is dumped, only the seek marker, and nothing read past the
+        // offset
+        assert_eq!(dump_stream(&dumper, &bytes), ("**\n".to_string(), 32));
+    }
+
+    #[test]
+    fn nibbles() {
+        let dumper = Dumper {
+            word_size: 2,
+            format: Format::Binary,
+            nibbles: true,
+            ..Default::default()
+        };
+        let fmt = dumper.line_format();
+        let line = build_line(3, b"Hel", 3, &fmt, &[], &[]);
+        assert_eq!(line.hex, "0100 1000  0110 0101  0110 1100");
+        assert_eq!(line.hex_width, line.hex.len());
+        let full = build_line(16, &[0; 16], 16, &fmt, &[], &[]);
+        assert_eq!(fmt.hex_length, full.hex_width);
+    }
+
+    #[test]
+    fn reverse_squeeze_count() {
+        let mut bytes = b"counted".to_vec();
+        bytes.resize(96, 0);
+        bytes.extend_from_slice(b"end");
+        let dumper = Dumper {
+            squeeze_count: true,
+            ..Default::default()
+        };
+        let text = dump(&dumper, &bytes);
+        assert!(text.contains("* (skipped 0x40 bytes of 00)\n"), "{}", text);
+        assert_eq!(reversed(&text, "count", "*", "**"), bytes);
+    }
+
+    #[test]
+    fn sparse_raw_format() {
+        let path = std::env::temp_dir().join(format!("rxdump-raw-{}", std::process::id()));
+        std::fs::write(&path, b"not sparse at all").unwrap();
+        let dumper = Dumper {
+            format: Format::C,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let file = File::open(&path).unwrap();
+        dumper.dump_sparse(&file, &mut out).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            dump(&dumper, b"not sparse at all")
+        );
+    }
+
+    #[test]
+    fn reverse_sparse() {
+        let path = std::env::temp_dir().join(format!("rxdump-sparse-{}", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        let mut bytes = b"head".to_vec();
+        bytes.resize(0x100, 0);
+        file.write_all(&bytes).unwrap();
+        file.seek(SeekFrom::Start(0x100000)).unwrap();
+        file.write_all(b"tail").unwrap();
+        file.set_len(0x200000).unwrap();
+        bytes.resize(0x100000, 0);
+        bytes.extend_from_slice(b"tail");
+        bytes.resize(0x200000, 0);
+
+        let mut out = Vec::new();
+        let file = File::open(&path).unwrap();
+        Dumper::default().dump_sparse(&file, &mut out).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.contains("*\n*"), "{}", text);
+        let back = reversed(&text, "sparse", "*", "**");
+        assert!(back == bytes, "{} {}", back.len(), text);
+    }
+
+    #[test]
+    fn reverse_rle() {
+        let mut bytes = b"ab".to_vec();
+        bytes.resize(80, 0);
+        bytes.extend_from_slice(b"xyz");
+        let dumper = Dumper {
+            rle: true,
+            ..Default::default()
+        };
+        let text = dump(&dumper, &bytes);
+        assert!(text.contains("\n* 00 x "), "{}", text);
+        assert_eq!(reversed(&text, "rle", "*", "**"), bytes);
+    }
+
+    #[test]
+    fn search_without_color() {
+        let dumper = Dumper {
+            search: vec![0x6f, 0x72],
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(&dumper, b"world"),
+            "00000000  77 [6f] [72] 6c 64                               |w[o][r]ld|\n"
+        );
+    }
+
+    #[test]
+    fn diff_without_color() {
+        let mut out = Vec::new();
+        Dumper::default()
+            .diff(&b"hello world"[..], &b"hellO world!"[..], &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "-00000000  68 65 6c 6c [6f] 20 77 6f 72 6c 64               |hell[o] world|\n\
+             +00000000  68 65 6c 6c [4f] 20 77 6f 72 6c 64 [21]          |hell[O] world[!]|\n"
+        );
+    }
+
+    #[test]
+    fn dump_cursor() {
+        assert_eq!(
+            dump(&Dumper::default(), b"Hello, rxdump!\n\x00\x01"),
+            "00000000  48 65 6c 6c 6f 2c 20 72 78 64 75 6d 70 21 0a 00  |Hello, rxdump!..|\n\
+             00000010  01                                               |.|\n"
+        );
+    }
+}
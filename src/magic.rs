@@ -0,0 +1,40 @@
+// SIGNATURES are the magic numbers of well known file types, as the offset
+// of the magic number in the file, the magic number and the type.
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x7fELF", "ELF executable"),
+    (0, b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (0, b"\xff\xd8\xff", "JPEG image"),
+    (0, b"GIF87a", "GIF image"),
+    (0, b"GIF89a", "GIF image"),
+    (0, b"%PDF-", "PDF document"),
+    (0, b"PK\x03\x04", "ZIP archive"),
+    (0, b"PK\x05\x06", "ZIP archive (empty)"),
+    (0, b"\x1f\x8b", "gzip compressed data"),
+    (0, b"BZh", "bzip2 compressed data"),
+    (0, b"\xfd7zXZ\x00", "xz compressed data"),
+    (0, b"\x28\xb5\x2f\xfd", "zstd compressed data"),
+    (0, b"7z\xbc\xaf\x27\x1c", "7-zip archive"),
+    (257, b"ustar", "tar archive"),
+    (0, b"\xcf\xfa\xed\xfe", "Mach-O executable"),
+    (0, b"\xce\xfa\xed\xfe", "Mach-O executable"),
+    (
+        0,
+        b"\xca\xfe\xba\xbe",
+        "Java class or Mach-O universal binary",
+    ),
+    (0, b"\x00asm", "WebAssembly module"),
+    (0, b"SQLite format 3\x00", "SQLite database"),
+    (0, b"RIFF", "RIFF container (WAV, AVI or WebP)"),
+    (0, b"\xef\xbb\xbf", "UTF-8 text with BOM"),
+    (0, b"#!", "script"),
+    (0, b"MZ", "DOS/PE executable"),
+];
+
+// identify guesses the type of a file from its first bytes, in head, None
+// is returned if none of the signatures match.
+pub fn identify(head: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| head.get(*offset..).is_some_and(|h| h.starts_with(magic)))
+        .map(|(_, _, name)| *name)
+}
@@ -1,211 +1,1356 @@
-use clap::Parser;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::SeekFrom;
-
-const LINE_BYTES: usize = 16;
+use clap::{CommandFactory, Parser, ValueEnum};
+use rxdump::charset::Charset;
+use rxdump::{
+    as_u64, c_identifier, decode_hex, reverse, Dumper, Endian, Format, Input, Interpret, LineCrc,
+    Mode, Output, Radix,
+};
+use std::fs::OpenOptions;
+use std::io::{BufReader, BufWriter, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(version,about,long_about = None)]
 struct Cli {
-    /// Input filename
-    filename: String,
+    /// Input filenames, use '-' to read from stdin
+    #[arg(required_unless_present_any = ["completions", "files_from"])]
+    filenames: Vec<String>,
+
+    /// Also dump the files named in FILE, one per line, use '-' to read the
+    /// names from stdin
+    #[arg(long, value_name = "FILE", require_equals = true)]
+    files_from: Option<String>,
+
+    /// Dump every regular file under the directories given, in name order
+    #[arg(short = 'R', long, action)]
+    recursive: bool,
+
+    /// Follow symbolic links met by --recursive, instead of skipping them
+    #[arg(long, action, requires = "recursive")]
+    follow_symlinks: bool,
+
+    /// Separate the names read with --files-from by NUL bytes instead of
+    /// newlines, like 'find -print0' writes them
+    #[arg(long, action, requires = "files_from")]
+    null: bool,
+
+    /// Print a completion script for SHELL to stdout
+    #[arg(long, value_name = "SHELL", hide = true)]
+    completions: Option<clap_complete::Shell>,
+
+    /// Continue the offsets of each file from the end of the previous one
+    #[arg(long, action)]
+    continuous: bool,
+
+    /// Compare the input with FILE, printing a diff of the two
+    #[arg(long, value_name = "FILE")]
+    diff: Option<String>,
 
     /// Number of bytes in a "word"
     #[arg(short, long, value_name = "BYTES")]
     word_size: Option<usize>,
 
-    /// Offset from which to start reading file (hexadecimal value prefix with '0x')
-    #[arg(short, long, value_name = "BYTES")]
+    /// Number of bytes per line, must be a multiple of the word size, lines
+    /// are widened to a single word if the word size is larger
+    #[arg(short, long, value_name = "BYTES", default_value_t = rxdump::LINE_BYTES)]
+    cols: usize,
+
+    /// Fit as many bytes per line as the terminal is wide, in whole words,
+    /// groups and splits. The default line width is kept if stdout is not a
+    /// terminal
+    #[arg(long, action, conflicts_with = "cols")]
+    auto_cols: bool,
+
+    /// Offset from which to start reading file (hexadecimal value prefix with '0x'),
+    /// with an optional K, M, G, Ki, Mi or Gi suffix, a leading '-' counts back
+    /// from the end of the file
+    #[arg(short, long, value_name = "BYTES", allow_hyphen_values = true)]
     offset: Option<String>,
 
-    /// Limit of bytes to read from file (hexadecimal value prefix with '0x')
+    /// Skip BYTES bytes of the input, offsets in the output then count from
+    /// there, where --offset shows the real position in the file
+    #[arg(long, value_name = "BYTES", require_equals = true)]
+    skip: Option<String>,
+
+    /// Print offsets from the start of the dump, so the first line is at 0
+    #[arg(long, action)]
+    relative: bool,
+
+    /// Offset at which to stop reading file (hexadecimal value prefix with
+    /// '0x'), with an optional K, M, G, Ki, Mi or Gi suffix. It counts from the
+    /// start of the file, however the start of the dump is given
     #[arg(short, long, value_name = "BYTES")]
     limit: Option<String>,
 
+    /// Number of bytes to read from the start of the dump, with an optional
+    /// K, M, G, Ki, Mi or Gi suffix
+    #[arg(short = 'n', long, value_name = "BYTES", conflicts_with = "limit")]
+    length: Option<String>,
+
     #[arg(long = "show-empty-lines", action)]
     show_empty_lines: bool,
+
+    /// Print every line, instead of squeezing runs of identical lines to '*'
+    #[arg(long = "no-squeeze", action)]
+    no_squeeze: bool,
+
+    /// Marker printed in place of squeezed lines, and read back by --reverse
+    #[arg(long, value_name = "STR", default_value = "*")]
+    squeeze_marker: String,
+
+    /// Show the number of bytes skipped with the squeeze marker, and the byte
+    /// filling them if they repeat a single one
+    #[arg(long, action)]
+    squeeze_count: bool,
+
+    /// Show the byte and the length of the run it fills with the squeeze
+    /// marker of lines repeating a single byte, like '* 00 x 4096'
+    #[arg(long, action)]
+    rle: bool,
+
+    /// Marker printed when starting after, or stopping before, the end of the
+    /// input, and skipped by --reverse
+    #[arg(long, value_name = "STR", default_value = "**")]
+    seek_marker: String,
+
+    /// Use uppercase hexadecimal for bytes and offsets
+    #[arg(short = 'U', long, action)]
+    upper: bool,
+
+    /// Split each hex or binary byte in two nibbles, like '0 f' or '0000 1111'
+    #[arg(long, action)]
+    nibbles: bool,
+
+    /// Do not print the ASCII column
+    #[arg(long = "no-ascii", action)]
+    no_ascii: bool,
+
+    /// Show control chars by their name, like NUL or LF, in a wider ASCII column
+    #[arg(long, action)]
+    ctrl_names: bool,
+
+    /// Print only the offset and ASCII column of each line, without hex
+    #[arg(long, action, conflicts_with = "no_ascii")]
+    text: bool,
+
+    /// Character set used to render the text column
+    #[arg(long, value_enum, default_value_t = Charset::Ascii)]
+    charset: Charset,
+
+    /// Add a column with each word decoded as a value of TYPE, using --endian,
+    /// or print the range dumped decoded as a single value for the utf16,
+    /// time, ip and uuid types
+    #[arg(long, value_name = "TYPE", value_enum)]
+    interpret: Option<Interpret>,
+
+    /// Decode --interpret=uuid as a Microsoft GUID, with its first three
+    /// fields little endian
+    #[arg(long, action)]
+    uuid_mixed_endian: bool,
+
+    /// Number of bytes grouped between separators, a multiple of the word
+    /// size, which still sets the byte order and --interpret
+    #[arg(long, value_name = "BYTES", require_equals = true)]
+    group: Option<usize>,
+
+    /// Split the hex and ASCII columns by a bar every BYTES bytes, a multiple
+    /// of the group size
+    #[arg(long, value_name = "BYTES", require_equals = true)]
+    split_at: Option<usize>,
+
+    /// Separator between the groups of the hex column
+    #[arg(long, value_name = "STR", default_value = " ")]
+    group_sep: String,
+
+    /// Char shown in the text column for non-printable bytes
+    #[arg(long, value_name = "CHAR", default_value_t = '.')]
+    unprintable_char: char,
+
+    /// Decode the text column as UTF-8, shorthand for '--charset utf8'
+    #[arg(long, action, conflicts_with = "charset")]
+    utf8: bool,
+
+    /// Colorize bytes by class (null, printable, whitespace, other)
+    #[arg(long, value_name = "WHEN", default_value = "auto")]
+    color: ColorWhen,
+
+    /// Only dim null bytes instead of colorizing every byte, when --color allows colors
+    #[arg(long, action)]
+    dim_zeros: bool,
+
+    /// Tint the hex column of each line from blue to red by the entropy of
+    /// its bytes, when --color allows colors
+    #[arg(long, action, conflicts_with = "dim_zeros")]
+    entropy_color: bool,
+
+    /// Append a checksum of the bytes of each line, after the ASCII column
+    #[arg(
+        long,
+        value_name = "KIND",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "crc16"
+    )]
+    line_crc: Option<LineCrc>,
+
+    /// Append the runs of at least MINLEN printable characters found in each
+    /// line, with their offsets, after the ASCII column
+    #[arg(
+        long,
+        value_name = "MINLEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "4"
+    )]
+    inline_strings: Option<usize>,
+
+    /// Byte order of words, for the hex column and --interpret values.
+    /// The ASCII column always stays in file order
+    #[arg(long, value_name = "ORDER", value_enum, default_value = "big")]
+    endian: Endian,
+
+    /// Output plain hex without offsets, spacing or ASCII, 60 hex chars per line
+    #[arg(short, long, action, conflicts_with = "format")]
+    plain: bool,
+
+    /// Output format
+    #[arg(long, value_name = "FORMAT", default_value = "hexdump")]
+    format: Format,
+
+    /// Output like 'od -A x -t x1z', shorthand for '--format od'
+    #[arg(long, action, conflicts_with_all = ["format", "binary", "plain", "word_size"])]
+    od: bool,
+
+    /// Output like 'xxd', shorthand for '--format xxd'
+    #[arg(long, action, conflicts_with_all = ["format", "binary", "plain", "od"])]
+    xxd: bool,
+
+    /// Show bytes as binary digits, shorthand for '--format binary'
+    #[arg(short = 'B', long, action, conflicts_with = "format")]
+    binary: bool,
+
+    /// Print offsets as octal, like 'od'
+    #[arg(long = "octal-offset", action)]
+    octal_offset: bool,
+
+    /// Print offsets as zero-padded decimal
+    #[arg(long = "decimal-offset", action, conflicts_with = "octal_offset")]
+    decimal_offset: bool,
+
+    /// Number of digits of offsets, by default sized to fit the file
+    #[arg(long, value_name = "DIGITS")]
+    offset_width: Option<usize>,
+
+    /// Prefix offsets by their radix, like 0x00000020 for hex offsets
+    #[arg(long, action)]
+    offset_prefix: bool,
+
+    /// Follow each offset by the same offset in decimal, like 00000020 (32)
+    #[arg(long, action)]
+    dual_offset: bool,
+
+    /// Follow each offset by its size in KiB, MiB or GiB, like 00100000 (1.0 MiB)
+    #[arg(long, action)]
+    human_offset: bool,
+
+    /// Number the lines from 1 in place of their offsets
+    #[arg(
+        long,
+        action,
+        conflicts_with_all = ["offset_prefix", "dual_offset", "human_offset", "no_offset"]
+    )]
+    line_numbers: bool,
+
+    /// Do not print the offset column
+    #[arg(long = "no-offset", action)]
+    no_offset: bool,
+
+    /// Show the progress of the dump on stderr, if it is a terminal
+    #[arg(long, action)]
+    progress: bool,
+
+    /// Print the file type, guessed from the magic number at the offset, before dumping
+    #[arg(long, action)]
+    identify: bool,
+
+    /// Print the number of bytes dumped, and their range, to stderr at the end
+    #[arg(long, action)]
+    summary: bool,
+
+    /// Print the names of offsets listed in FILE, one 'offset name' pair per
+    /// line, as comments before the lines holding them
+    #[arg(long, value_name = "FILE", require_equals = true)]
+    annotate: Option<String>,
+
+    /// Print a header numbering the byte positions above the first line
+    #[arg(long, action)]
+    ruler: bool,
+
+    /// Dump only the bytes of RANGE, given as START:LEN or START-END, may be
+    /// repeated to dump several ranges of each file
+    #[arg(
+        long,
+        value_name = "RANGE",
+        require_equals = true,
+        conflicts_with_all = ["offset", "limit", "length", "tail", "diff"]
+    )]
+    range: Vec<String>,
+
+    /// Dump only the last BYTES bytes of the file, overriding --offset
+    #[arg(long, value_name = "BYTES", require_equals = true)]
+    tail: Option<String>,
+
+    /// Do not print the header row of the csv format
+    #[arg(long = "no-header", action)]
+    no_header: bool,
+
+    /// Escape every byte of the python format, printable ones too
+    #[arg(long, action)]
+    escape_all: bool,
+
+    /// Variable name used by the c, rust, python and go formats, defaults to the sanitized
+    /// filename, in uppercase for rust
+    #[arg(long, value_name = "NAME")]
+    name: Option<String>,
+
+    /// Print a CRC32 of the dumped bytes after the last line
+    #[arg(long, action)]
+    crc32: bool,
+
+    /// Print a SHA-256 digest of the dumped bytes after the last line
+    #[arg(long, action)]
+    sha256: bool,
+
+    /// Check the CRC32 of the dumped bytes against HEX, failing if it differs
+    #[arg(long, value_name = "HEX", require_equals = true)]
+    expect_crc32: Option<String>,
+
+    /// Check the SHA-256 digest of the dumped bytes against HEX, failing if
+    /// it differs
+    #[arg(long, value_name = "HEX", require_equals = true)]
+    expect_sha256: Option<String>,
+
+    /// Print a histogram of byte values instead of dumping
+    #[arg(long, action)]
+    histogram: bool,
+
+    /// Print the entropy of each block of BLOCK bytes instead of dumping
+    #[arg(
+        long,
+        value_name = "BLOCK",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "256"
+    )]
+    entropy: Option<usize>,
+
+    /// Print runs of at least MINLEN printable characters instead of dumping
+    #[arg(
+        long,
+        value_name = "MINLEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "4"
+    )]
+    strings: Option<usize>,
+
+    /// Print the number of occurrences of the byte value BYTE (hexadecimal
+    /// value prefix with '0x') instead of dumping
+    #[arg(long, value_name = "BYTE", require_equals = true)]
+    count: Option<String>,
+
+    /// Highlight every occurrence of the byte value BYTE (hexadecimal value
+    /// prefix with '0x'), between brackets without color
+    #[arg(long, value_name = "BYTE")]
+    highlight: Option<String>,
+
+    /// Highlight occurrences of the byte pattern HEX, e.g. "dead beef"
+    #[arg(long, value_name = "HEX")]
+    search: Option<String>,
+
+    /// Print the offset of each occurrence of the byte pattern HEX instead of
+    /// dumping, and the number of them on stderr
+    #[arg(
+        long,
+        value_name = "HEX",
+        require_equals = true,
+        conflicts_with = "search"
+    )]
+    find: Option<String>,
+
+    /// Only print lines matching --search, with a line of context around
+    #[arg(long, action, requires = "search")]
+    search_only: bool,
+
+    /// Start at the first occurrence of the byte pattern HEX, from --offset on
+    #[arg(long, value_name = "HEX", require_equals = true)]
+    start_at: Option<String>,
+
+    /// Stop after the line on which the byte pattern HEX, e.g. "dead beef", ends
+    #[arg(long, value_name = "HEX", require_equals = true)]
+    stop_at: Option<String>,
+
+    /// Keep reading at the end of the file, dumping lines as they are
+    /// appended. The last, partial, line is dumped on Ctrl-C
+    #[arg(short, long, action)]
+    follow: bool,
+
+    /// Decompress the input with gzip, done for files ending in '.gz' anyway
+    #[arg(short = 'z', long, action)]
+    gunzip: bool,
+
+    /// Memory map the input file instead of reading it, ignored for stdin
+    #[arg(long, action)]
+    mmap: bool,
+
+    /// Skip the holes of sparse files without reading them, showing each as
+    /// the squeeze marker with its size. Ignored where holes can not be found
+    #[arg(long, action, conflicts_with = "mmap")]
+    sparse: bool,
+
+    /// Write the bytes HEX at OFFSET into the file before dumping, may be
+    /// repeated. Without a range to dump, the lines patched are dumped
+    #[arg(
+        long,
+        value_name = "OFFSET=HEX",
+        require_equals = true,
+        conflicts_with_all = ["diff", "reverse", "skip", "gunzip"]
+    )]
+    patch: Vec<String>,
+
+    /// Allow --patch to write past the end of the file, growing it
+    #[arg(long, action, requires = "patch")]
+    grow: bool,
+
+    /// Reverse operation: convert a dump, as written by rxdump or xxd, back into binary
+    #[arg(short, long, action)]
+    reverse: bool,
+
+    /// Write the bytes dumped, as they are, to FILE instead of dumping them
+    #[arg(
+        long,
+        value_name = "FILE",
+        require_equals = true,
+        conflicts_with_all = ["output", "diff", "reverse", "histogram", "entropy", "strings", "count", "find"]
+    )]
+    carve: Option<String>,
+
+    /// Write the dump, or the reversed binary, to FILE instead of stdout
+    #[arg(short = 'O', long, value_name = "FILE")]
+    output: Option<String>,
 }
 
-struct Line {
-    ascii: String,
-    hex: String,
-    start_offset: usize,
-    hex_length: usize,
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorWhen {
+    Auto,
+    Always,
+    Never,
 }
 
-impl Line {
-    fn print(&self) {
-        println!(
-            "{:08x}  {: <3$} |{}|",
-            self.start_offset, self.hex, self.ascii, self.hex_length
-        );
+// Range is the part of each input to dump, as given on the command line
+struct Range {
+    offset: Option<u64>,
+    limit: u64,
+    // length is the number of bytes to dump from the start, setting the limit
+    // once the start is known
+    length: Option<u64>,
+    // from_end is the number of bytes before the end of the input to start at
+    from_end: Option<u64>,
+}
+
+// AppError is an error ending the run, each kind exits with its own code
+enum AppError {
+    // Open is a file that could not be opened
+    Open(String, std::io::Error),
+    // Create is an output file that could not be created
+    Create(String, std::io::Error),
+    // Seek is a position in the input that could not be reached
+    Seek(String),
+    // Parse is an invalid value given on the command line
+    Parse(String),
+    // Dump is an error while dumping the named file
+    Dump(String, std::io::Error),
+    // Diff is an error while comparing the named files
+    Diff(String, String, std::io::Error),
+    // Reverse is an error while reversing the named file
+    Reverse(String, std::io::Error),
+    // Patch is an error while patching the named file
+    Patch(String, std::io::Error),
+    // Reported stands in for errors already reported, exiting with the code
+    // of the last one
+    Reported(u8),
+}
+
+impl AppError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            AppError::Open(..) | AppError::Create(..) => 2,
+            AppError::Seek(_) | AppError::Parse(_) => 3,
+            AppError::Dump(..) | AppError::Diff(..) => 1,
+            AppError::Reverse(..) => 4,
+            AppError::Patch(..) => 5,
+            AppError::Reported(code) => *code,
+        }
     }
 }
 
-fn main() {
-    let cli = Cli::parse();
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AppError::Open(name, e) => write!(f, "could not open {}: {}", name, e),
+            AppError::Create(name, e) => write!(f, "could not create {}: {}", name, e),
+            AppError::Seek(msg) | AppError::Parse(msg) => write!(f, "{}", msg),
+            AppError::Dump(name, e) => write!(f, "while dumping {}: {}", name, e),
+            AppError::Diff(a, b, e) => write!(f, "while comparing {} and {}: {}", a, b, e),
+            AppError::Reverse(name, e) => write!(f, "while reversing {}: {}", name, e),
+            AppError::Patch(name, e) => write!(f, "while patching {}: {}", name, e),
+            AppError::Reported(_) => Ok(()),
+        }
+    }
+}
 
-    let word_size: usize = cli.word_size.unwrap_or(1);
-    let line_words: usize = LINE_BYTES / word_size;
-    let hex_length: usize = word_size * 2 * line_words + line_words;
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if !matches!(e, AppError::Reported(_)) {
+                eprintln!("{}", e);
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+// run does what the command line asks for
+fn run(mut cli: Cli) -> Result<(), AppError> {
+    if let Some(shell) = cli.completions {
+        clap_complete::generate(shell, &mut Cli::command(), "rxdump", &mut std::io::stdout());
+        return Ok(());
+    }
+    if let Some(list) = cli.files_from.as_deref() {
+        let names = read_filenames(list, cli.null)?;
+        cli.filenames.extend(names);
+    }
+
+    if cli.reverse {
+        let mut out = Output::create(cli.output.as_deref())
+            .map_err(|e| AppError::Create(cli.output.clone().unwrap_or_default(), e))?;
+        for filename in &cli.filenames {
+            let input = open_file(&cli, filename)?;
+            reverse(
+                BufReader::new(input),
+                &mut out,
+                &cli.squeeze_marker,
+                &cli.seek_marker,
+            )
+            .map_err(|e| AppError::Reverse(filename.clone(), e))?;
+        }
+        return Ok(());
+    }
+
+    let format = if cli.binary {
+        Format::Binary
+    } else if cli.od {
+        Format::Od
+    } else if cli.xxd {
+        Format::Xxd
+    } else {
+        cli.format
+    };
+
+    let interpret = match cli.interpret {
+        Some(Interpret::Uuid) if cli.uuid_mixed_endian => Some(Interpret::Guid),
+        _ if cli.uuid_mixed_endian => {
+            return Err(AppError::Parse(
+                "invalid --uuid-mixed-endian: only applies to --interpret=uuid".to_string(),
+            ))
+        }
+        interpret => interpret,
+    };
+
+    // the word size defaults to the size of the interpreted values, xxd
+    // groups bytes in pairs
+    let default_word_size = if format == Format::Xxd { 2 } else { 1 };
+    let word_size: usize = cli
+        .word_size
+        .unwrap_or(interpret.map_or(default_word_size, |i| i.size()));
+    if let Some(interpret) = interpret {
+        if word_size != interpret.size() {
+            return Err(AppError::Parse(format!(
+                "invalid word size {} for --interpret: values are {} bytes",
+                word_size,
+                interpret.size()
+            )));
+        }
+    }
+    let cols = line_cols(word_size, cli.cols)?;
+    if let Some(group) = cli.group {
+        if group == 0 || !group.is_multiple_of(word_size) {
+            return Err(AppError::Parse(format!(
+                "invalid group size {}: must be a non-zero multiple of the word size {}",
+                group, word_size
+            )));
+        }
+    }
+    if let Some(split_at) = cli.split_at {
+        let group = cli.group.unwrap_or(word_size);
+        if split_at == 0 || !split_at.is_multiple_of(group) {
+            return Err(AppError::Parse(format!(
+                "invalid split size {}: must be a non-zero multiple of the group size {}",
+                split_at, group
+            )));
+        }
+    }
+    if cli.nibbles && matches!(format, Format::Octal | Format::Decimal) {
+        return Err(AppError::Parse(
+            "invalid --nibbles: bytes must be shown as hex or binary digits".to_string(),
+        ));
+    }
+    if cli.entropy == Some(0) {
+        return Err(AppError::Parse("invalid entropy block size 0".to_string()));
+    }
+    let highlight = parse_byte("highlight", cli.highlight.as_deref())?;
+    let count = parse_byte("count", cli.count.as_deref())?;
+    let expect_crc32 = cli
+        .expect_crc32
+        .as_deref()
+        .map(|s| {
+            u32::from_str_radix(s.trim_start_matches("0x"), 16).map_err(|_| {
+                AppError::Parse(format!("invalid crc32 '{}': expected 8 hex digits", s))
+            })
+        })
+        .transpose()?;
+    let expect_sha256 = cli
+        .expect_sha256
+        .as_deref()
+        .map(|s| {
+            decode_hex(s)
+                .and_then(|digest| digest.try_into().ok())
+                .ok_or_else(|| {
+                    AppError::Parse(format!("invalid sha256 '{}': expected 64 hex digits", s))
+                })
+        })
+        .transpose()?;
+    let search = match cli.find.as_deref() {
+        Some(find) => parse_pattern("find", Some(find))?,
+        None => parse_pattern("search", cli.search.as_deref())?,
+    };
+    let start_at = parse_pattern("start", cli.start_at.as_deref())?;
+    let stop_at = parse_pattern("stop", cli.stop_at.as_deref())?;
+    let color = match cli.color {
+        ColorWhen::Auto => cli.output.is_none() && std::io::stdout().is_terminal(),
+        ColorWhen::Always => true,
+        ColorWhen::Never => false,
+    };
 
-    let mut buffer = [0; LINE_BYTES];
-    let mut offset: usize = 0;
-    let mut limit: usize = 0;
-    let mut last_was_all_zero = false;
-    let mut skipped_lines = 0;
-    let skip_zero_lines = !cli.show_empty_lines;
+    let mut dumper = Dumper {
+        word_size,
+        cols,
+        // xxd prints every line
+        squeeze: !(cli.show_empty_lines || cli.no_squeeze || format == Format::Xxd),
+        squeeze_marker: cli.squeeze_marker.clone(),
+        squeeze_count: cli.squeeze_count,
+        rle: cli.rle,
+        seek_marker: cli.seek_marker.clone(),
+        format,
+        plain: cli.plain,
+        offset_radix: if cli.octal_offset {
+            Radix::Octal
+        } else if cli.decimal_offset {
+            Radix::Decimal
+        } else {
+            Radix::Hex
+        },
+        offsets: !cli.no_offset,
+        offset_prefix: cli.offset_prefix,
+        line_numbers: cli.line_numbers,
+        human_offset: cli.human_offset,
+        relative: cli.relative,
+        upper: cli.upper,
+        nibbles: cli.nibbles,
+        hex: !cli.text,
+        ascii: !cli.no_ascii,
+        charset: if cli.utf8 { Charset::Utf8 } else { cli.charset },
+        ctrl_names: cli.ctrl_names,
+        unprintable: cli.unprintable_char,
+        group_sep: cli.group_sep.clone(),
+        group: cli.group.unwrap_or(0),
+        split_at: cli.split_at.unwrap_or(0),
+        // a range decoded as a whole has no column of values
+        interpret: interpret.filter(|i| !i.region()),
+        highlight,
+        color: color && !cli.dim_zeros && !cli.entropy_color,
+        dim_zeros: color && cli.dim_zeros,
+        entropy_color: color && cli.entropy_color,
+        endian: cli.endian,
+        line_crc: cli.line_crc,
+        inline_strings: cli.inline_strings.map_or(0, |min| min.max(1)),
+        header: !cli.no_header,
+        crc32: cli.crc32,
+        sha256: cli.sha256,
+        expect_crc32,
+        expect_sha256,
+        mode: if cli.carve.is_some() {
+            Mode::Carve
+        } else if cli.histogram {
+            Mode::Histogram
+        } else if let Some(block) = cli.entropy {
+            Mode::Entropy(block)
+        } else if let Some(min) = cli.strings {
+            Mode::Strings(min.max(1))
+        } else if let Some(value) = count {
+            Mode::Count(value)
+        } else if cli.find.is_some() {
+            Mode::Find
+        } else if let Some(interpret) = interpret.filter(|i| i.region()) {
+            Mode::Decode(interpret)
+        } else {
+            Mode::Dump
+        },
+        // bars are 80 columns wide when not written to a terminal
+        width: terminal_columns().unwrap_or(80),
+        search,
+        search_only: cli.search_only,
+        start_at,
+        stop_at,
+        escape_all: cli.escape_all,
+        ruler: cli.ruler,
+        identify: cli.identify,
+        summary: cli.summary,
+        annotations: match cli.annotate.as_deref() {
+            Some(filename) => read_annotations(filename)?,
+            None => Vec::new(),
+        },
+        ..Default::default()
+    };
+    let mut range = Range {
+        offset: None,
+        limit: 0,
+        length: None,
+        from_end: None,
+    };
 
     // calculate limit if passed as argument
-    if cli.limit.is_some() {
-        let limit_str = cli.limit.unwrap();
-        limit = match as_u64(&limit_str) {
-            Err(e) => {
-                eprintln!("invalid limit value '{}': {}", &limit_str, e);
-                std::process::exit(3);
-            }
-            Ok(v) => v.try_into().unwrap(),
-        };
+    if let Some(limit_str) = cli.limit.as_deref() {
+        range.limit = as_u64(limit_str)
+            .map_err(|e| AppError::Parse(format!("invalid limit value '{}': {}", limit_str, e)))?;
+    }
+    if let Some(length_str) = cli.length.as_deref() {
+        let invalid =
+            |e: String| AppError::Parse(format!("invalid length value '{}': {}", length_str, e));
+        match as_u64(length_str).map_err(invalid)? {
+            0 => return Err(invalid("nothing to dump".to_string())),
+            length => range.length = Some(length),
+        }
     }
 
-    // open file
-    let mut f = match File::open(&cli.filename) {
-        Err(e) => {
-            println!("could not open {}: {}", cli.filename, e);
-            std::process::exit(2);
+    // possition to offset if passed, a negative offset is resolved once the
+    // file is opened
+    if let Some(offset_str) = cli.offset.as_deref() {
+        let (negative, value) = match offset_str.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, offset_str),
+        };
+        let v = as_u64(value).map_err(|e| {
+            AppError::Parse(format!("invalid offset value '{}': {}", offset_str, e))
+        })?;
+        if negative {
+            range.from_end = Some(v);
+        } else {
+            range.offset = Some(v);
         }
-        Ok(f) => f,
+    }
+    if let Some(skip_str) = cli.skip.as_deref() {
+        dumper.skip = as_u64(skip_str)
+            .map_err(|e| AppError::Parse(format!("invalid skip value '{}': {}", skip_str, e)))?;
+    }
+    if let Some(tail_str) = cli.tail.as_deref() {
+        let v = as_u64(tail_str)
+            .map_err(|e| AppError::Parse(format!("invalid tail value '{}': {}", tail_str, e)))?;
+        range.offset = None;
+        range.from_end = Some(v);
+    }
+
+    let patches: Vec<(u64, Vec<u8>)> = cli
+        .patch
+        .iter()
+        .map(|p| parse_patch(p))
+        .collect::<Result<_, _>>()?;
+    let whole =
+        cli.offset.is_none() && cli.limit.is_none() && cli.length.is_none() && cli.tail.is_none();
+    let ranges = if !patches.is_empty() && cli.range.is_empty() && whole {
+        // the lines holding the patched bytes
+        let cols = dumper.cols as u64;
+        patches
+            .iter()
+            .map(|(offset, bytes)| {
+                let start = offset - offset % cols;
+                Range {
+                    offset: (start > 0).then_some(start),
+                    limit: (offset + bytes.len() as u64)
+                        .div_ceil(cols)
+                        .saturating_mul(cols),
+                    length: None,
+                    from_end: None,
+                }
+            })
+            .collect()
+    } else if cli.range.is_empty() {
+        vec![range]
+    } else {
+        cli.range
+            .iter()
+            .map(|r| parse_range(r))
+            .collect::<Result<_, _>>()?
     };
 
-    // possition to offset if passed
-    if cli.offset.is_some() {
-        let offset_str = cli.offset.unwrap();
-        let pos = match as_u64(&offset_str) {
-            Err(e) => {
-                eprintln!("invalid offset value '{}': {}", &offset_str, e);
-                std::process::exit(3);
-            }
-            Ok(v) => v,
+    let output = cli.carve.as_deref().or(cli.output.as_deref());
+    let mut out = BufWriter::new(
+        Output::create(output)
+            .map_err(|e| AppError::Create(output.unwrap_or("").to_string(), e))?,
+    );
+    // the carved bytes are written without any headers or separators
+    let headers = cli.carve.is_none();
+    if let Some(other) = cli.diff.as_deref() {
+        // the inputs may differ in size, leaving no common end for a
+        // negative offset or tail to count from
+        if ranges[0].from_end.is_some() {
+            return Err(AppError::Parse(
+                "invalid --diff: can not start from the end of the inputs".to_string(),
+            ));
+        }
+        let [filename] = cli.filenames.as_slice() else {
+            return Err(AppError::Parse(format!(
+                "invalid --diff: expected a single input to compare, got {}",
+                cli.filenames.len()
+            )));
         };
-        match f.seek(SeekFrom::Start(pos)) {
-            Err(e) => {
-                eprintln!(
-                    "could not seek to pos {} on file {}: {}",
-                    pos, cli.filename, e
-                );
-                std::process::exit(3);
+        let f = open_input(&cli, &mut dumper, &ranges[0], filename)?;
+        let g = open_file(&cli, other)?;
+        if let Err(e) = dumper.diff(f, g, &mut out) {
+            if e.kind() != std::io::ErrorKind::BrokenPipe {
+                return Err(AppError::Diff(filename.clone(), other.to_string(), e));
             }
-            Ok(n) => offset += usize::try_from(n).unwrap(),
         }
-        println!("**") // indicate not at SOF
-    };
+        return Ok(());
+    }
 
-    // read through file
-    loop {
-        let mut n = match f.read(&mut buffer) {
-            Ok(size) => size,
-            Err(e) => {
-                eprintln!("while reading bufer: {}", e);
-                0
+    // dump each file in turn, reporting failures but carrying on with the
+    // next file
+    let mut status = None;
+    let mut filenames = Vec::new();
+    for filename in &cli.filenames {
+        if !cli.recursive || !Path::new(filename).is_dir() {
+            filenames.push(filename.clone());
+            continue;
+        }
+        let mut visited = Vec::new();
+        if let Err(e) = walk(
+            Path::new(filename),
+            cli.follow_symlinks,
+            &mut visited,
+            &mut filenames,
+        ) {
+            eprintln!("{}", e);
+            status = Some(e.exit_code());
+        }
+    }
+    let many = filenames.len() > 1 || cli.files_from.is_some() || cli.recursive;
+    let mut base_offset = 0;
+    'files: for (i, filename) in filenames.iter().enumerate() {
+        if many && headers {
+            let sep = if i > 0 { "\n" } else { "" };
+            if writeln!(out, "{}==> {} <==", sep, filename).is_err() {
+                break;
             }
-        };
-        if n == 0 && skipped_lines == 0 {
-            break;
         }
-        if limit != 0 && (offset + n) >= limit {
-            n = limit - offset
+        dumper.base_offset = if cli.continuous { base_offset } else { 0 };
+        if let Err(e) = patch_file(filename, &patches, cli.grow) {
+            eprintln!("{}", e);
+            status = Some(e.exit_code());
+            continue;
         }
+        for (j, range) in ranges.iter().enumerate() {
+            if j > 0 && headers && writeln!(out, "--").is_err() {
+                break 'files;
+            }
+            let f = match open_input(&cli, &mut dumper, range, filename) {
+                Ok(f) if ranges.len() > 1 && !matches!(f, Input::File(_)) => Err(AppError::Seek(
+                    "dumping several ranges needs a seekable input file".to_string(),
+                )),
+                result => result,
+            };
+            let f = match f {
+                Err(e) => {
+                    eprintln!("{}", e);
+                    status = Some(e.exit_code());
+                    continue 'files;
+                }
+                Ok(f) => f,
+            };
+            if let (0, Input::File(file)) = (j, &f) {
+                base_offset += file.metadata().map_or(0, |m| m.len());
+            }
 
-        offset += n;
-        let is_all_zero = skip_zero_lines && all_zero(&buffer);
+            if let Err(e) = dump_input(&cli, &dumper, f, &mut out) {
+                if e.kind() == std::io::ErrorKind::BrokenPipe {
+                    break 'files;
+                }
+                let e = AppError::Dump(filename.clone(), e);
+                eprintln!("{}", e);
+                status = Some(e.exit_code());
+                continue 'files;
+            }
+        }
+    }
+    status.map_or(Ok(()), |code| Err(AppError::Reported(code)))
+}
 
-        // skip multiple all_zero lines, if they are complete lines
-        if is_all_zero && last_was_all_zero && (n == buffer.len()) {
-            skipped_lines += 1;
+// dump_input dumps f the way it is best read: memory mapped if asked for,
+// seeking if it is a file and reading through it otherwise.
+fn dump_input<W: Write>(cli: &Cli, dumper: &Dumper, f: Input, out: &mut W) -> std::io::Result<()> {
+    match f {
+        Input::File(f) if cli.mmap && !dumper.follow => {
+            // fall back to reading the file if it can not be mapped
+            // SAFETY: the map is only read, and only for the duration of
+            // the dump
+            match unsafe { memmap2::Mmap::map(&f) } {
+                Ok(data) => dumper.dump_slice(&data, out),
+                Err(_) => dumper.dump(f, out),
+            }
+        }
+        Input::File(f) if cli.sparse => dumper.dump_sparse(&f, out),
+        Input::File(f) => dumper.dump(f, out),
+        Input::Stdin(s) => dumper.dump_unseekable(s.lock(), out),
+        Input::Gzip(g) => dumper.dump_unseekable(g, out),
+    }
+}
+
+// line_cols returns the number of bytes per line for words of word_size
+// bytes, given cols. A line holds at least one word, and a whole number of
+// them.
+fn line_cols(word_size: usize, cols: usize) -> Result<usize, AppError> {
+    let line = cols.max(word_size);
+    if word_size == 0 || cols == 0 || !line.is_multiple_of(word_size) {
+        return Err(AppError::Parse(format!(
+            "invalid word size {} for {} bytes per line: cols must be a non-zero multiple of the word size",
+            word_size, cols
+        )));
+    }
+    Ok(line)
+}
+
+// read_annotations reads the 'offset name' lines of filename, sorted by
+// offset. Empty lines and lines starting with '#' are ignored.
+fn read_annotations(filename: &str) -> Result<Vec<(u64, String)>, AppError> {
+    let text =
+        std::fs::read_to_string(filename).map_err(|e| AppError::Open(filename.to_string(), e))?;
+    let mut annotations = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
             continue;
         }
+        let (offset, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let offset = as_u64(offset).map_err(|e| {
+            AppError::Parse(format!(
+                "{}: line {}: invalid offset: {}",
+                filename,
+                i + 1,
+                e
+            ))
+        })?;
+        annotations.push((offset, name.trim().to_string()));
+    }
+    annotations.sort_by_key(|(offset, _)| *offset);
+    Ok(annotations)
+}
 
-        if skipped_lines > 0 {
-            skipped_lines = 0;
-            println!("*") // indicate one or more skipped lines
+// walk adds the regular files under dir to filenames, in name order, and
+// descends into its subdirectories. Symbolic links are skipped unless
+// followed, visited holds the directories walked so far, so links looping
+// back to them are not followed again.
+fn walk(
+    dir: &Path,
+    follow: bool,
+    visited: &mut Vec<PathBuf>,
+    filenames: &mut Vec<String>,
+) -> Result<(), AppError> {
+    let open = |e| AppError::Open(dir.display().to_string(), e);
+    let real = dir.canonicalize().map_err(open)?;
+    if visited.contains(&real) {
+        return Ok(());
+    }
+    visited.push(real);
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .and_then(|entries| entries.map(|e| e.map(|e| e.path())).collect())
+        .map_err(open)?;
+    entries.sort();
+    for path in entries {
+        let Ok(meta) = path.symlink_metadata() else {
+            continue;
+        };
+        if meta.is_symlink() && !follow {
+            continue;
+        }
+        if path.is_dir() {
+            walk(&path, follow, visited, filenames)?;
+        } else if path.is_file() {
+            filenames.push(path.display().to_string());
         }
+    }
+    Ok(())
+}
 
-        build_line(offset, &buffer, n, word_size, hex_length).print();
+// read_filenames reads the names listed in filename, or on stdin for '-',
+// separated by newlines or NUL bytes. Empty names are ignored.
+fn read_filenames(filename: &str, null: bool) -> Result<Vec<String>, AppError> {
+    let mut data = Vec::new();
+    let result = if filename == "-" {
+        std::io::stdin().lock().read_to_end(&mut data)
+    } else {
+        std::fs::File::open(filename).and_then(|mut f| f.read_to_end(&mut data))
+    };
+    result.map_err(|e| AppError::Open(filename.to_string(), e))?;
+    let sep = if null { b'\0' } else { b'\n' };
+    Ok(data
+        .split(|b| *b == sep)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
 
-        last_was_all_zero = is_all_zero;
+// parse_patch parses the value of --patch, OFFSET=HEX, into the offset and
+// the bytes to write there. The bytes must end at an offset that fits a u64.
+fn parse_patch(s: &str) -> Result<(u64, Vec<u8>), AppError> {
+    let invalid = |e: String| AppError::Parse(format!("invalid patch '{}': {}", s, e));
+    let (offset, hex) = s
+        .split_once('=')
+        .ok_or_else(|| invalid("expected OFFSET=HEX".to_string()))?;
+    let offset = as_u64(offset).map_err(|e| invalid(e.to_string()))?;
+    let bytes = match decode_hex(hex.trim_start_matches("0x")) {
+        Some(bytes) if !bytes.is_empty() => bytes,
+        _ => return Err(invalid("expected hex bytes".to_string())),
+    };
+    if offset.checked_add(bytes.len() as u64).is_none() {
+        return Err(invalid(
+            "the patch ends past the largest offset".to_string(),
+        ));
+    }
+    Ok((offset, bytes))
+}
 
-        if offset == limit {
-            println!("**"); // indicate end before EOF
-            break;
+// patch_file writes the patches into filename, each at its offset. None are
+// written unless all of them are within the file, or grow is set.
+fn patch_file(filename: &str, patches: &[(u64, Vec<u8>)], grow: bool) -> Result<(), AppError> {
+    if patches.is_empty() {
+        return Ok(());
+    }
+    if filename == "-" {
+        return Err(AppError::Seek("can not patch stdin".to_string()));
+    }
+    let mut f = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(filename)
+        .map_err(|e| AppError::Open(filename.to_string(), e))?;
+    let size = f
+        .metadata()
+        .map_err(|e| AppError::Patch(filename.to_string(), e))?
+        .len();
+    for (offset, bytes) in patches {
+        if !grow && offset + bytes.len() as u64 > size {
+            return Err(AppError::Seek(format!(
+                "can not patch {} bytes at offset 0x{:x} of {}: past end of file (size 0x{:x}), see --grow",
+                bytes.len(),
+                offset,
+                filename,
+                size
+            )));
         }
     }
+    for (offset, bytes) in patches {
+        eprintln!(
+            "patching {} bytes at offset 0x{:x} of {}",
+            bytes.len(),
+            offset,
+            filename
+        );
+        f.seek(SeekFrom::Start(*offset))
+            .and_then(|_| f.write_all(bytes))
+            .map_err(|e| AppError::Patch(filename.to_string(), e))?;
+    }
+    Ok(())
+}
+
+// parse_byte parses the byte value of a byte option, what names the option
+// in errors
+fn parse_byte(what: &str, s: Option<&str>) -> Result<Option<u8>, AppError> {
+    s.map(|s| match as_u64(s).map(u8::try_from) {
+        Ok(Ok(b)) => Ok(b),
+        _ => Err(AppError::Parse(format!(
+            "invalid {} value '{}': expected a byte value",
+            what, s
+        ))),
+    })
+    .transpose()
+}
+
+// parse_pattern parses the hex bytes of a pattern option, what names the
+// pattern in errors. No pattern is returned as an empty one.
+fn parse_pattern(what: &str, hex: Option<&str>) -> Result<Vec<u8>, AppError> {
+    let Some(hex) = hex else {
+        return Ok(Vec::new());
+    };
+    match decode_hex(hex.trim_start_matches("0x")) {
+        Some(pattern) if !pattern.is_empty() => Ok(pattern),
+        _ => Err(AppError::Parse(format!(
+            "invalid {} pattern '{}': expected hex bytes",
+            what, hex
+        ))),
+    }
+}
+
+// parse_range parses the value of --range, START:LEN or START-END, into the
+// range between START and END.
+fn parse_range(s: &str) -> Result<Range, AppError> {
+    let invalid = |e: String| AppError::Parse(format!("invalid range '{}': {}", s, e));
+    let (start, end) = if let Some((start, len)) = s.split_once(':') {
+        let start = as_u64(start).map_err(invalid)?;
+        let len = as_u64(len).map_err(invalid)?;
+        let end = start
+            .checked_add(len)
+            .ok_or_else(|| invalid("the range ends past the largest offset".to_string()))?;
+        (start, end)
+    } else if let Some((start, end)) = s.split_once('-') {
+        (
+            as_u64(start).map_err(invalid)?,
+            as_u64(end).map_err(invalid)?,
+        )
+    } else {
+        return Err(invalid("expected START:LEN or START-END".to_string()));
+    };
+    if end <= start {
+        return Err(invalid("the range is empty".to_string()));
+    }
+    Ok(Range {
+        offset: (start > 0).then_some(start),
+        limit: end,
+        length: None,
+        from_end: None,
+    })
 }
 
-// line_from_buffer will iterate over the the first "n" bytes of the buffer
-// in "word_sized" chunks and add them to both the hexadecimal and the ascii output-strings.
-fn build_line(
-    end_offset: usize,
-    buf: &[u8],
-    n: usize,
-    word_size: usize,
-    hex_length: usize,
-) -> Line {
-    let mut hex: String = String::new();
-    let mut ascii: String = String::new();
-    for (i, word) in buf[0..n].chunks(word_size).enumerate() {
-        hex += &word_as_hex(word);
-        if i < n {
-            hex += " "
+// open_input opens filename and sets up dumper for it: the range to dump,
+// the offset width and name derived from the file.
+fn open_input(
+    cli: &Cli,
+    dumper: &mut Dumper,
+    range: &Range,
+    filename: &str,
+) -> Result<Input, AppError> {
+    let mut f = open_file(cli, filename)?;
+    dumper.offset = range.offset;
+    dumper.limit = range.limit;
+
+    // seek back from the end of the file, the limit still counts from the start
+    if let Some(back) = range.from_end {
+        let Input::File(file) = &mut f else {
+            return Err(AppError::Seek(
+                "reading back from the end needs a seekable input file".to_string(),
+            ));
+        };
+        let start = match i64::try_from(back).map(|b| file.seek(SeekFrom::End(-b))) {
+            Ok(Ok(start)) => start,
+            _ => {
+                return Err(AppError::Seek(format!(
+                    "can not start {} bytes before the end of {}: the file is smaller",
+                    back, filename
+                )))
+            }
+        };
+        eprintln!("starting at offset 0x{:x}", start);
+        // offsets count from the skipped bytes
+        let start = start.saturating_sub(dumper.skip);
+        dumper.offset = Some(start);
+    }
+
+    // the start must be a position in the input
+    if let Err(e) = dumper.start_pos() {
+        return Err(AppError::Seek(e.to_string()));
+    }
+
+    // the length counts from wherever the dump starts
+    if let Some(length) = range.length {
+        dumper.limit = dumper.offset.unwrap_or(0).saturating_add(length);
+    }
+
+    let size = match &f {
+        Input::File(file) => file
+            .metadata()
+            .ok()
+            .map(|m| m.len().saturating_sub(dumper.skip)),
+        Input::Stdin(_) | Input::Gzip(_) => None,
+    };
+
+    // seeking past the end succeeds, there is just nothing to dump, unless
+    // following the file as it grows
+    if let (Some(offset), Some(size)) = (dumper.offset, size) {
+        if offset >= size && !cli.follow {
+            eprintln!(
+                "offset 0x{:x} is past end of file (size 0x{:x})",
+                offset, size
+            );
         }
-        ascii += &word_as_ascii(word);
     }
-    Line {
-        ascii,
-        hex,
-        start_offset: end_offset - n,
-        hex_length,
+
+    // widen offsets to fit the end of the file, unless set explicitly
+    dumper.offset_width = match (cli.offset_width, size) {
+        (Some(w), _) => w,
+        // od pads offsets to six digits, whatever the size of the file
+        (None, _) if dumper.format == Format::Od => 6,
+        (None, Some(size)) => dumper
+            .offset_radix
+            .digits(dumper.base_offset + size)
+            .max(dumper.offset_radix.offset_width()),
+        (None, None) => 0,
+    };
+
+    // the decimal offsets are right justified to fit the end of the file
+    if cli.dual_offset {
+        dumper.dual_offset = match size {
+            Some(size) => Radix::Decimal.digits(dumper.base_offset + size),
+            None => Radix::Decimal.offset_width(),
+        };
+    }
+
+    // the lines are fitted to the terminal once the offsets are sized
+    if cli.auto_cols && cli.output.is_none() {
+        if let Some(width) = terminal_columns() {
+            dumper.cols = fit_cols(dumper, width);
+        }
     }
+
+    // the progress is relative to the end of the file or the limit
+    dumper.progress = (cli.progress && std::io::stderr().is_terminal()).then(|| {
+        let end = match (dumper.limit, size) {
+            (0, size) => size.unwrap_or(0),
+            (limit, Some(size)) => limit.min(size),
+            (limit, None) => limit,
+        };
+        end.saturating_sub(dumper.offset.unwrap_or(0))
+    });
+
+    // following only makes sense for a file that may grow
+    dumper.follow = cli.follow && matches!(f, Input::File(_));
+    dumper.name = cli.name.clone().unwrap_or_else(|| match dumper.format {
+        // rust constants are in uppercase
+        Format::Rust => c_identifier(filename).to_uppercase(),
+        _ => c_identifier(filename),
+    });
+    Ok(f)
 }
 
-// as_u64 parses a string to a u64, if the string is prefixed with '0x' the string
-// will be parsed as hexadecimal, if not it will be parsed as decimal.
-fn as_u64(s: &String) -> Result<u64, std::num::ParseIntError> {
-    if s.starts_with("0x") {
-        let h = s.trim_start_matches("0x");
-        u64::from_str_radix(h, 16)
+// open_file opens filename, decompressing it with gzip when asked to or
+// when the name ends in ".gz"
+fn open_file(cli: &Cli, filename: &str) -> Result<Input, AppError> {
+    let f = Input::open(filename).map_err(|e| AppError::Open(filename.to_string(), e))?;
+    if cli.gunzip || filename.ends_with(".gz") {
+        Ok(f.gunzip())
     } else {
-        u64::from_str_radix(s.as_str(), 10)
+        Ok(f)
     }
 }
 
-// all_zero will return true if all bytes in a byte array is zero
-fn all_zero(line: &[u8]) -> bool {
-    line.iter().position(|&x| x != 0) == None
+// fit_cols returns the largest number of bytes per line, in steps of whole
+// splits, groups or words, that keeps lines of dumper within width columns.
+// A line holds a single step if even that does not fit.
+fn fit_cols(dumper: &mut Dumper, width: usize) -> usize {
+    let step = match (dumper.split_at, dumper.group) {
+        (0, 0) => dumper.word_size,
+        (0, group) => group,
+        (split, _) => split,
+    };
+    let mut cols = step;
+    loop {
+        dumper.cols = cols + step;
+        if dumper.line_format().line_width() > width {
+            return cols;
+        }
+        cols += step;
+    }
 }
 
-// word_as_hex converts an array of bytes to a hex string, it will pad
-// the hexvalue of each byte witn '0'
-fn word_as_hex(word: &[u8]) -> String {
-    let mut wds: String = String::new();
-    for (_, byte) in word.iter().enumerate() {
-        let letter = format!("{:02x}", byte);
-        wds += &letter;
+// terminal_columns returns the width of the terminal on stdout, from COLUMNS
+// if set, None if stdout is not a terminal
+fn terminal_columns() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    if let Some(columns) = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok()) {
+        return Some(columns);
     }
-    wds
+    window_columns()
 }
 
-// word_as_ascii convets an array of bytes to a printable ascii string
-// replacing non-printable chars with '.'
-fn word_as_ascii(word: &[u8]) -> String {
-    let mut a: String = String::new();
-    for (_, b) in word.iter().enumerate() {
-        if *b >= 0x20 && *b < 0x7f {
-            // printable chars
-            a.push(*b as char)
-        } else {
-            a.push('.')
-        }
+// window_columns asks the terminal on stdout for its number of columns
+#[cfg(unix)]
+fn window_columns() -> Option<usize> {
+    // SAFETY: winsize is plain old data, filled in by the ioctl on success
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let res = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (res == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+#[cfg(not(unix))]
+fn window_columns() -> Option<usize> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_past_largest_offset() {
+        assert!(matches!(
+            parse_patch("0xffffffffffffffff=00"),
+            Err(AppError::Parse(_))
+        ));
+        assert!(matches!(parse_patch("0x10=dead"), Ok((0x10, _))));
+    }
+
+    #[test]
+    fn diff_negative_offset() {
+        let cli = Cli::try_parse_from(["rxdump", "--offset=-8", "--diff", "b", "a"]).unwrap();
+        assert!(matches!(run(cli), Err(AppError::Parse(_))));
+    }
+
+    #[test]
+    fn diff_tail() {
+        let cli = Cli::try_parse_from(["rxdump", "--tail=8", "--diff", "b", "a"]).unwrap();
+        assert!(matches!(run(cli), Err(AppError::Parse(_))));
+    }
+
+    #[test]
+    fn diff_single_input() {
+        let cli = Cli::try_parse_from(["rxdump", "--files-from=/dev/null", "--diff", "b"]).unwrap();
+        assert!(matches!(run(cli), Err(AppError::Parse(_))));
+        let cli = Cli::try_parse_from(["rxdump", "--diff", "c", "a", "b"]).unwrap();
+        assert!(matches!(run(cli), Err(AppError::Parse(_))));
+    }
+
+    #[test]
+    fn word_size_and_cols() {
+        // smaller words keep the width of the line, larger ones widen it
+        assert!(matches!(line_cols(4, 16), Ok(16)));
+        assert!(matches!(line_cols(32, 16), Ok(32)));
+    }
+
+    #[test]
+    fn word_size_not_dividing_cols() {
+        assert!(matches!(line_cols(3, 16), Err(AppError::Parse(_))));
+        assert!(matches!(line_cols(0, 16), Err(AppError::Parse(_))));
     }
-    a
 }
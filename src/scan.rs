@@ -0,0 +1,162 @@
+use crate::{is_printable, printable};
+use std::io::Write;
+
+// Histogram counts the occurrences of each byte value
+pub struct Histogram {
+    counts: [u64; 256],
+    total: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            counts: [0; 256],
+            total: 0,
+        }
+    }
+}
+
+impl Histogram {
+    pub fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.counts[*b as usize] += 1;
+        }
+        self.total += bytes.len() as u64;
+    }
+
+    // print prints a line per byte value seen, most frequent first, with a
+    // bar scaled to fit within width columns.
+    pub fn print<W: Write>(&self, out: &mut W, width: usize) -> std::io::Result<()> {
+        let mut values: Vec<usize> = (0..256).filter(|v| self.counts[*v] > 0).collect();
+        values.sort_by(|a, b| self.counts[*b].cmp(&self.counts[*a]).then(a.cmp(b)));
+        let max = values.first().map_or(0, |v| self.counts[*v]);
+        // value, char, count and percentage take up 32 columns
+        let bar_width = width.saturating_sub(32).max(1) as u64;
+        for v in values {
+            let count = self.counts[v];
+            let bar = "#".repeat((count * bar_width).div_ceil(max) as usize);
+            writeln!(
+                out,
+                "{:02x}  {}  {:>12}  {:>7.3}%  {}",
+                v,
+                printable(v as u8),
+                count,
+                count as f64 * 100.0 / self.total as f64,
+                bar
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Entropy computes the Shannon entropy of consecutive blocks of bytes,
+// printing a line per block as soon as the block is complete.
+pub struct Entropy {
+    block: usize,
+    counts: [u64; 256],
+    fill: usize,
+    offset: u64,
+}
+
+impl Entropy {
+    // new creates an Entropy for blocks of size block, starting at offset
+    pub fn new(block: usize, offset: u64) -> Entropy {
+        Entropy {
+            block,
+            counts: [0; 256],
+            fill: 0,
+            offset,
+        }
+    }
+
+    pub fn update<W: Write>(&mut self, out: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+        for b in bytes {
+            self.counts[*b as usize] += 1;
+            self.fill += 1;
+            if self.fill == self.block {
+                self.print_block(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    // finish prints the entropy of the last, partial, block if any
+    pub fn finish<W: Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        if self.fill > 0 {
+            self.print_block(out)?;
+        }
+        Ok(())
+    }
+
+    fn print_block<W: Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "{:08x}: {:.3}", self.offset, shannon(&self.counts))?;
+        self.offset += self.fill as u64;
+        self.fill = 0;
+        self.counts = [0; 256];
+        Ok(())
+    }
+}
+
+// shannon returns the Shannon entropy, in bits per byte (0.0 to 8.0), of
+// the bytes counted in counts.
+pub fn shannon(counts: &[u64; 256]) -> f64 {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut entropy = 0.0;
+    for c in counts.iter().filter(|c| **c > 0) {
+        let p = *c as f64 / total as f64;
+        entropy -= p * p.log2();
+    }
+    entropy
+}
+
+// Strings finds runs of printable ascii of at least min bytes, printing
+// each run with its starting offset once it ends.
+pub struct Strings {
+    min: usize,
+    offset: u64,
+    run: String,
+}
+
+impl Strings {
+    // new creates a Strings for runs of at least min bytes, starting at offset
+    pub fn new(min: usize, offset: u64) -> Strings {
+        Strings {
+            min,
+            offset,
+            run: String::new(),
+        }
+    }
+
+    pub fn update<W: Write>(&mut self, out: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+        for b in bytes {
+            if is_printable(*b) {
+                self.run.push(*b as char);
+            } else {
+                self.end_run(out)?;
+            }
+            self.offset += 1;
+        }
+        Ok(())
+    }
+
+    // finish prints a run still in progress at the end of the input
+    pub fn finish<W: Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        self.end_run(out)
+    }
+
+    fn end_run<W: Write>(&mut self, out: &mut W) -> std::io::Result<()> {
+        if self.run.len() >= self.min {
+            writeln!(
+                out,
+                "{:08x}  {}",
+                self.offset - self.run.len() as u64,
+                self.run
+            )?;
+        }
+        self.run.clear();
+        Ok(())
+    }
+}
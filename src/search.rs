@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+// Matcher finds occurrences of a byte pattern in a stream of bytes, it
+// keeps the last bytes seen so matches may span the chunks passed to
+// update.
+pub struct Matcher {
+    pattern: Vec<u8>,
+    window: VecDeque<u8>,
+    // pos is the offset of the next byte passed to update
+    pos: u64,
+}
+
+impl Matcher {
+    // new creates a Matcher for pattern, with the stream starting at offset
+    pub fn new(pattern: &[u8], offset: u64) -> Matcher {
+        Matcher {
+            pattern: pattern.to_vec(),
+            window: VecDeque::with_capacity(pattern.len()),
+            pos: offset,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pattern.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_empty()
+    }
+
+    // update feeds bytes to the matcher, calling found with the start offset
+    // of each match ending within bytes.
+    pub fn update(&mut self, bytes: &[u8], mut found: impl FnMut(u64)) {
+        if self.pattern.is_empty() {
+            self.pos += bytes.len() as u64;
+            return;
+        }
+        for b in bytes {
+            if self.window.len() == self.pattern.len() {
+                self.window.pop_front();
+            }
+            self.window.push_back(*b);
+            self.pos += 1;
+            if self.window.len() == self.pattern.len() && self.window.iter().eq(&self.pattern) {
+                found(self.pos - self.pattern.len() as u64);
+            }
+        }
+    }
+}
+
+// find reads from reader up to the end of the first occurrence of pattern,
+// returning the offset of its start from where the reader was positioned.
+// None is returned if the reader ends first, all of it is consumed then.
+pub fn find<R: BufRead>(reader: &mut R, pattern: &[u8]) -> std::io::Result<Option<u64>> {
+    let mut matcher = Matcher::new(pattern, 0);
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        let mut found = None;
+        let mut used = buf.len();
+        for (i, b) in buf.iter().enumerate() {
+            matcher.update(std::slice::from_ref(b), |start| found = Some(start));
+            if found.is_some() {
+                used = i + 1;
+                break;
+            }
+        }
+        reader.consume(used);
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{read_full, Dump, Dumper, Mode, BLOCK_LINES};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+
+impl Dumper {
+    // dump_sparse dumps file like dump does, skipping the holes of a sparse
+    // file instead of reading the zeros they hold. Only the whole lines of a
+    // hole are skipped, each hole printed as the squeeze marker followed by
+    // its size. Files, or filesystems, without holes are read as usual, as
+    // are dumps needing every byte, to search or checksum them.
+    pub fn dump_sparse<W: Write>(&self, file: &File, out: &mut W) -> std::io::Result<()> {
+        let size = file.metadata()?.len().saturating_sub(self.skip);
+        let start = self.offset.unwrap_or(0).min(size);
+        let needs_bytes = self.mode != Mode::Dump
+            || !self.search.is_empty()
+            || !self.start_at.is_empty()
+            || !self.stop_at.is_empty()
+            || self.crc32
+            || self.sha256
+            || self.expect_crc32.is_some()
+            || self.expect_sha256.is_some()
+            || self.identify
+            || self.follow;
+        let mut dump = Dump::new(self, start);
+        // probing for a hole moves the file offset, dump only seeks with an
+        // offset set, so the fallbacks to it go first
+        if needs_bytes || dump.raw || seek_hole(file, self.skip + start).is_err() {
+            return self.dump(file, out);
+        }
+        let end = match self.limit {
+            0 => size,
+            limit => limit.min(size),
+        };
+
+        dump.start(out)?;
+        let mut pos = start;
+        while pos < end {
+            let (hole_start, hole_end) = self.next_hole(file, start, pos, end)?;
+            if self.dump_data(file, out, &mut dump, pos, hole_start)? {
+                return dump.finish(out);
+            }
+            if hole_end > hole_start {
+                dump.hole(out, hole_end - hole_start)?;
+            }
+            pos = hole_end;
+        }
+        if dump.skipped_lines > 0 {
+            dump.line(out, &[], 0)?;
+        }
+        dump.finish(out)
+    }
+
+    // next_hole returns the first hole at or after pos, before end, trimmed
+    // to the whole lines, counted from start, within it. No hole is
+    // returned as the empty range at end.
+    fn next_hole(
+        &self,
+        file: &File,
+        start: u64,
+        pos: u64,
+        end: u64,
+    ) -> std::io::Result<(u64, u64)> {
+        let cols = self.cols as u64;
+        let mut pos = pos;
+        loop {
+            let hole = seek_hole(file, self.skip + pos)?.saturating_sub(self.skip);
+            let hole_start = start + (hole.max(start) - start).div_ceil(cols) * cols;
+            if hole_start >= end {
+                return Ok((end, end));
+            }
+            let data = seek_data(file, self.skip + hole_start)?.saturating_sub(self.skip);
+            // a hole running to the end skips the last, partial, line too
+            let hole_end = match data {
+                data if data >= end => end,
+                data => start + (data - start) / cols * cols,
+            };
+            if hole_end > hole_start {
+                return Ok((hole_start, hole_end));
+            }
+            pos = data;
+        }
+    }
+
+    // dump_data dumps the bytes of file from pos up to end, true is returned
+    // when the dump should stop.
+    fn dump_data<W: Write>(
+        &self,
+        mut file: &File,
+        out: &mut W,
+        dump: &mut Dump,
+        pos: u64,
+        end: u64,
+    ) -> std::io::Result<bool> {
+        file.seek(SeekFrom::Start(self.skip + pos))?;
+        let mut reader = BufReader::with_capacity(self.cols * BLOCK_LINES, file.take(end - pos));
+        let mut buffer = vec![0; self.cols];
+        loop {
+            let n = read_full(&mut reader, &mut buffer)?;
+            if n == 0 {
+                return Ok(false);
+            }
+            if dump.line(out, &buffer, n)? {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+// seek_hole returns the offset of the first hole of file at or after pos,
+// the end of the file counts as one.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn seek_hole(file: &File, pos: u64) -> std::io::Result<u64> {
+    lseek(file, pos, libc::SEEK_HOLE)
+}
+
+// seek_data returns the offset of the first data of file at or after pos,
+// the end of the file if there is none.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn seek_data(file: &File, pos: u64) -> std::io::Result<u64> {
+    match lseek(file, pos, libc::SEEK_DATA) {
+        Err(e) if e.raw_os_error() == Some(libc::ENXIO) => file.metadata().map(|m| m.len()),
+        result => result,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn lseek(file: &File, pos: u64, whence: libc::c_int) -> std::io::Result<u64> {
+    use std::os::fd::AsRawFd;
+    let pos = libc::off_t::try_from(pos)
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+    // SAFETY: lseek only moves the offset of the open file descriptor
+    let res = unsafe { libc::lseek(file.as_raw_fd(), pos, whence) };
+    if res < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(res as u64)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn seek_hole(_: &File, _: u64) -> std::io::Result<u64> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn seek_data(_: &File, _: u64) -> std::io::Result<u64> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}